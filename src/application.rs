@@ -1,30 +1,315 @@
 use crate::common::{self, *};
+use crate::clipboard::{Clipboard, OnClipboardChanged};
+use crate::command_line::CommandLine;
+use crate::executor::{Executor, UiHandle};
+use crate::i18n::Translations;
+use crate::palette::Palette;
+use crate::screen::{OnScreensChanged, Screen, ScreenWatcher};
+use crate::settings::Settings;
+use crate::shortcut::{KeySequence, ShortcutError, ShortcutHandle, ShortcutScope, Shortcuts};
+use crate::style::StyleWatcher;
+use crate::timer::{OnTimer, TimerHandle, Timers};
 
-use qt_core::{QCoreApplication, QCoreApplicationArgs};
+use qt_core::{QCoreApplication, QCoreApplicationArgs, QThread};
 use qt_core::QTimer;
 use qt_core::QString;
-use qt_core::ConnectionType;
-use qt_gui::QGuiApplication;
-use qt_widgets::QApplication;
+use qt_gui::{QGuiApplication, QPalette};
+use qt_network::{q_local_socket::LocalSocketState, QLocalServer, QLocalSocket};
+use qt_widgets::{QApplication, QMessageBox};
 
 use plygui_api::{controls, types};
 
 use std::borrow::Cow;
+use std::cell::RefCell;
 use std::process::exit;
-use std::any::TypeId;
+use std::rc::Rc;
+use std::any::{Any, TypeId};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Mutex;
+use std::time::Duration;
 
 const DEFAULT_FRAME_SLEEP_MS: u32 = 10;
 
+lazy_static! {
+    static ref WAKE_EVENT: i32 = unsafe { QEvent::register_event_type_0a() };
+    // the object of the running application that receives `WAKE_EVENT`. Held while posting, so the object cannot be deleted meanwhile
+    static ref WAKE_RECEIVER: Mutex<usize> = Mutex::new(0);
+}
+
+const SINGLE_INSTANCE_TIMEOUT_MS: i32 = 500;
+
 pub type Application = AApplication<QtApplication>;
+pub type OnAnotherInstance = Box<dyn FnMut(&mut dyn controls::Application, Vec<String>)>;
+pub type OnPanic = Box<dyn FnMut(&mut dyn controls::Application, &str) -> PanicAction>;
+
+/// What to do after a callback panicked. The panic never unwinds into Qt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PanicAction {
+    /// Ignore the panic and keep the event loop running.
+    Continue,
+    /// Show the panic message in an error box and keep the event loop running.
+    ShowMessage,
+    /// Print the panic message and exit the process with code 101, like an unhandled Rust panic.
+    Exit,
+}
 
 pub struct QtApplication {
-    _args: QCoreApplicationArgs,
-    inner: QBox<QApplication>,
-    timer: QBox<QTimer>,
+    filter: QBox<CustomEventFilter>,
+    waker: QBox<QObject>,
     pub(crate) queue: QBox<SlotNoArgs>,
+    command_line: CommandLine,
+    single_instance: Option<(QBox<QLocalServer>, QBox<SlotNoArgs>)>,
+    on_another_instance: Option<OnAnotherInstance>,
+    on_panic: Option<OnPanic>,
+    settings: Option<Settings>,
+    clipboard: Option<Box<Clipboard>>,
+    style_watcher: Option<StyleWatcher>,
+    screen_watcher: Option<ScreenWatcher>,
+    palette: Palette,
+    // the platform palette, saved before the first `set_palette` replaces it
+    system_palette: Option<CppBox<QPalette>>,
+    translations: Translations,
+    pub(crate) timers: Timers,
+    executor: Executor,
+    pub(crate) shortcuts: Shortcuts,
+    timer: QBox<QTimer>,
+    inner: QBox<QApplication>,
+    // `QApplication` keeps pointers into the args, so they must be dropped last
+    _args: QCoreApplicationArgs,
+}
+
+/// Queues commands for the UI thread and wakes the Qt event loop to run them right away.
+/// Commands sent through the bare `ApplicationBase` sender wait until something else wakes the loop.
+#[derive(Clone)]
+pub struct QtSender(mpsc::Sender<callbacks::OnFrame>);
+
+impl QtSender {
+    pub fn send(&self, cmd: callbacks::OnFrame) -> Result<(), mpsc::SendError<callbacks::OnFrame>> {
+        self.0.send(cmd).map(|_| QtApplication::wake())
+    }
 }
 
 impl QtApplication {
+    /// Posts a wake-up event to the running application, if there is one, so queued commands run right away.
+    /// Safe to call from any thread.
+    pub fn wake() {
+        let receiver = WAKE_RECEIVER.lock().unwrap();
+        if *receiver != 0 {
+            unsafe { QCoreApplication::post_event_2a(Ptr::from_raw(*receiver as *const QObject), QEvent::new(QEventType::from(*WAKE_EVENT)).into_ptr()) };
+        }
+    }
+    /// Same as `with_name`, but forces the `offscreen` platform plugin, so no display is needed.
+    /// Only this application gets `-platform offscreen`, the process environment is left alone.
+    pub fn with_name_offscreen<S: AsRef<str>>(name: S) -> Box<dyn controls::Application> {
+        let mut args = std::env::args_os().map(|arg| arg.to_string_lossy().into_owned().into_bytes()).collect::<Vec<_>>();
+        // a later `-platform` wins over one given on the command line
+        args.push(b"-platform".to_vec());
+        args.push(b"offscreen".to_vec());
+        Self::with_name_and_args(name.as_ref(), QCoreApplicationArgs::from(args))
+    }
+    /// Captures the current structure of every window and tray along with their container children.
+    pub fn snapshot(&self) -> Vec<crate::snapshot::SnapshotNode> {
+        ApplicationInner::roots(self).map(|member| crate::snapshot::SnapshotNode::from_member(member)).collect()
+    }
+    /// Same as `with_name`, but allows only one running instance per application name. If another instance is already running,
+    /// the command-line arguments are forwarded to it and the process exits. The first instance receives them in `on_another_instance`.
+    /// If the instance cannot listen for others, it logs a warning and runs as a plain application.
+    pub fn with_name_single_instance<S: AsRef<str>>(name: S) -> Box<dyn controls::Application> {
+        // per user, so instances of different users on one machine do not find each other
+        let user = std::env::var_os("USER").or_else(|| std::env::var_os("USERNAME")).map(|user| user.to_string_lossy().into_owned()).unwrap_or_default();
+        let key = QString::from_std_str(format!("plygui-qt-{}-{}", user, name.as_ref()));
+        let mut app = <Self as ApplicationInner>::with_name(name);
+        if unsafe { forward_to_instance(&key) } {
+            drop(app);
+            exit(0);
+        }
+        let selfptr = app.as_any_mut().downcast_mut::<Application>().unwrap() as *mut Application;
+        let server = unsafe { QLocalServer::new_0a() };
+        let mut listening = unsafe { server.listen_q_string(&key) };
+        if !listening {
+            // another instance may have started listening since we tried to connect
+            if unsafe { forward_to_instance(&key) } {
+                drop(app);
+                exit(0);
+            }
+            // nobody answers, so the socket file was left behind by a crashed instance
+            unsafe {
+                QLocalServer::remove_server(&key);
+                listening = server.listen_q_string(&key);
+            }
+        }
+        if !listening {
+            log::warn!("Cannot listen for other instances: {}", unsafe { server.error_string().to_std_string() });
+            return app;
+        }
+        let slot = unsafe { SlotNoArgs::new(NullPtr, move || common::catch_panic((), || accept_instances(selfptr))) };
+        unsafe { server.new_connection().connect(&slot) };
+        unsafe { &mut *selfptr }.inner_mut().single_instance = Some((server, slot));
+        app
+    }
+    pub fn on_another_instance(&mut self, cb: Option<OnAnotherInstance>) {
+        self.on_another_instance = cb;
+    }
+    /// Decides what happens when a callback panics. Without it the process exits, as it would on an unhandled panic.
+    pub fn on_panic(&mut self, cb: Option<OnPanic>) {
+        self.on_panic = cb;
+    }
+    /// Persistent settings of the application, created on first access.
+    pub fn settings(&mut self) -> &mut Settings {
+        self.settings.get_or_insert_with(Settings::new)
+    }
+    pub fn clipboard(&mut self) -> &mut Clipboard {
+        self.clipboard.get_or_insert_with(Clipboard::new)
+    }
+    pub fn on_clipboard_changed(&mut self, cb: Option<OnClipboardChanged>) {
+        self.clipboard().on_changed(cb);
+    }
+    pub fn set_stylesheet(&mut self, css: &str) {
+        crate::style::set_application_stylesheet(css);
+    }
+    pub fn stylesheet(&self) -> String {
+        unsafe { self.inner.style_sheet().to_std_string() }
+    }
+    /// Applies the stylesheet from `path`. With `watch`, it is re-applied every time the file changes, until another stylesheet is loaded.
+    pub fn load_stylesheet<P: AsRef<std::path::Path>>(&mut self, path: P, watch: bool) -> std::io::Result<()> {
+        let css = std::fs::read_to_string(path.as_ref())?;
+        self.style_watcher = if watch { Some(StyleWatcher::new(path.as_ref())?) } else { None };
+        self.set_stylesheet(&css);
+        Ok(())
+    }
+    pub fn screens(&self) -> Vec<Screen> {
+        crate::screen::screens()
+    }
+    /// Fires when a screen is added or removed, or its geometry or DPI changes.
+    pub fn on_screens_changed(&mut self, cb: Option<OnScreensChanged>) {
+        if self.screen_watcher.is_none() {
+            self.screen_watcher = Some(ScreenWatcher::new(&self.inner));
+        }
+        self.screen_watcher.as_mut().unwrap().on_change(cb);
+    }
+    pub(crate) fn screen_watcher_mut(&mut self) -> Option<&mut ScreenWatcher> {
+        self.screen_watcher.as_mut()
+    }
+    /// Applies the palette to all windows and trays. Members are notified through `MemberEvents::on_palette_changed`.
+    pub fn set_palette(&mut self, palette: Palette) {
+        if self.system_palette.is_none() {
+            self.system_palette = Some(unsafe { QGuiApplication::palette() });
+        }
+        crate::palette::apply(&palette, self.system_palette.as_ref().unwrap());
+        self.palette = palette;
+        crate::palette::notify_trays(unsafe { common::cast_qobject_mut::<Application>(&self.inner) }.unwrap());
+    }
+    pub fn palette(&self) -> &Palette {
+        &self.palette
+    }
+    pub fn translations(&self) -> &Translations {
+        &self.translations
+    }
+    pub fn translations_mut(&mut self) -> &mut Translations {
+        &mut self.translations
+    }
+    /// Switches the language and re-translates labels of all members, window menus and trays.
+    pub fn set_language(&mut self, language: &str) {
+        self.translations.set_language(language);
+        let app = unsafe { common::cast_qobject_mut::<Application>(&self.inner) }.unwrap();
+        for root in app.roots_mut() {
+            crate::i18n::retranslate(root, &self.translations);
+        }
+    }
+    pub fn tr(&self, source: &str) -> String {
+        self.translations.tr(source)
+    }
+    /// Runs `cb` once on the UI thread after `duration`.
+    pub fn set_timeout(&mut self, duration: Duration, cb: OnTimer) -> TimerHandle {
+        self.timers.add(duration, false, cb)
+    }
+    /// Runs `cb` on the UI thread every `duration` until the returned handle is cancelled.
+    pub fn set_interval(&mut self, duration: Duration, cb: OnTimer) -> TimerHandle {
+        self.timers.add(duration, true, cb)
+    }
+    pub fn cancel_timer(&mut self, handle: TimerHandle) -> bool {
+        self.timers.cancel(handle)
+    }
+    /// Restarts the timer with a new duration counting from now, e.g. to debounce input.
+    pub fn reschedule_timer(&mut self, handle: TimerHandle, duration: Duration) -> bool {
+        self.timers.reschedule(handle, duration)
+    }
+    pub fn is_timer_active(&self, handle: TimerHandle) -> bool {
+        self.timers.is_active(handle)
+    }
+    /// Binds `keys` to `action` within `scope`. The action gets the scope member, or the active window for application shortcuts.
+    pub fn add_shortcut(&mut self, keys: KeySequence, scope: ShortcutScope, action: callbacks::Action) -> Result<ShortcutHandle, ShortcutError> {
+        let scope_widget = self.shortcut_scope_widget(scope)?;
+        let windows = self.window_widgets();
+        self.shortcuts.add(&keys, scope, scope_widget, windows.as_slice(), action)
+    }
+    pub fn rebind_shortcut(&mut self, handle: ShortcutHandle, keys: KeySequence) -> Result<(), ShortcutError> {
+        self.shortcuts.rebind(handle, &keys)
+    }
+    pub fn remove_shortcut(&mut self, handle: ShortcutHandle) -> bool {
+        self.shortcuts.remove(handle)
+    }
+    /// The keys of the shortcut as shown to the user on this platform.
+    pub fn shortcut_text(&self, handle: ShortcutHandle) -> Option<String> {
+        self.shortcuts.text(handle)
+    }
+    /// Shows the keys of the shortcut next to a menu item of the member, found by `path` as in `MenuItemsEnabled`. Follows later rebinds.
+    pub fn show_shortcut_in_menu(&mut self, handle: ShortcutHandle, member: ids::Id, path: &[usize]) -> bool {
+        let item = match ApplicationInner::find_member(self, types::FindBy::Id(member)).and_then(|member| common::menu_action(member, path)) {
+            Some(item) => item,
+            None => return false,
+        };
+        self.shortcuts.show_in_menu(handle, item)
+    }
+    fn shortcut_scope_widget(&self, scope: ShortcutScope) -> Result<Option<QPtr<QWidget>>, ShortcutError> {
+        let id = match scope {
+            ShortcutScope::Application => return Ok(None),
+            ShortcutScope::Window(id) | ShortcutScope::Control(id) => id,
+        };
+        let member = ApplicationInner::find_member(self, types::FindBy::Id(id)).ok_or(ShortcutError::NotFound)?;
+        if !common::is_widget_member(member) {
+            return Err(ShortcutError::NotFound);
+        }
+        let widget = unsafe { QPtr::from_raw(common::cast_member_to_qwidget(member) as *const QWidget) };
+        Ok(Some(match scope {
+            ShortcutScope::Window(_) => unsafe { widget.window() },
+            _ => widget,
+        }))
+    }
+    fn window_widgets(&self) -> Vec<Ptr<QWidget>> {
+        let base = &unsafe { common::cast_qobject_mut::<Application>(&self.inner) }.unwrap().base;
+        base.windows.iter().map(|window| unsafe { Ptr::from_raw(common::cast_member_to_qwidget(window.as_member()) as *const QWidget) }).collect()
+    }
+    pub(crate) fn executor_mut(&mut self) -> &mut Executor {
+        &mut self.executor
+    }
+    /// Handle for other threads to send work and results to the UI thread, e.g. from a background async runtime.
+    pub fn ui_handle(&self) -> UiHandle {
+        UiHandle::new(self.sender())
+    }
+    pub fn command_line(&mut self) -> &mut CommandLine {
+        &mut self.command_line
+    }
+    pub fn sender(&self) -> QtSender {
+        let base = & unsafe { common::cast_qobject::<Application>(&self.inner) }.unwrap().base;
+        QtSender(base.sender().clone())
+    }
+    /// Runs the Qt event loop until the application quits, then closes the remaining windows and trays and returns the exit code.
+    /// Unlike `start()` it gives control back, so the `Application` can be dropped and a new one created in the same process.
+    pub fn run(&mut self) -> i32 {
+        let code = unsafe { QApplication::exec() };
+        self.close_roots();
+        code
+    }
+    fn close_roots(&mut self) {
+        let base = &mut unsafe { common::cast_qobject_mut::<Application>(&mut self.inner) }.unwrap().base;
+        for mut window in base.windows.drain(..) {
+            window.as_any_mut().downcast_mut::<crate::window::Window>().unwrap().inner_mut().inner_mut().inner_mut().inner_mut().close(true);
+        }
+        for mut tray in base.trays.drain(..) {
+            tray.as_any_mut().downcast_mut::<crate::tray::Tray>().unwrap().inner_mut().close(true);
+        }
+    }
     pub fn maybe_exit(&mut self) -> bool {
         let base = &mut unsafe { common::cast_qobject_mut::<Application>(&mut self.inner) }.unwrap().base;
         if base.windows.len() < 1 && base.trays.len() < 1 {
@@ -38,7 +323,13 @@ impl QtApplication {
 
 impl<O: controls::Application> NewApplicationInner<O> for QtApplication {
     fn with_uninit_params(u: &mut mem::MaybeUninit<O>, name: &str) -> Self {
-        let mut args = QCoreApplicationArgs::new();
+        Self::with_uninit_args(u, name, QCoreApplicationArgs::new())
+    }
+}
+
+impl QtApplication {
+    fn with_uninit_args<O: controls::Application>(u: &mut mem::MaybeUninit<O>, name: &str, mut args: QCoreApplicationArgs) -> Self {
+        crate::logging::install();
         let (arg1, arg2) = args.get();
         let inner = unsafe { QApplication::new_2a(arg1, arg2) };
         unsafe { 
@@ -49,6 +340,22 @@ impl<O: controls::Application> NewApplicationInner<O> for QtApplication {
             _args: args,
             inner: inner,
             timer: unsafe { QTimer::new_0a() },
+            filter: CustomEventFilter::new(|_, _| false),
+            waker: unsafe { QObject::new_0a() },
+            command_line: CommandLine::new(),
+            single_instance: None,
+            on_another_instance: None,
+            on_panic: None,
+            settings: None,
+            clipboard: None,
+            style_watcher: None,
+            screen_watcher: None,
+            palette: Palette::System,
+            system_palette: None,
+            translations: Default::default(),
+            timers: Default::default(),
+            executor: Default::default(),
+            shortcuts: Default::default(),
             queue: unsafe { SlotNoArgs::new(NullPtr, move || {}) },
         };
         let selfptr = u as *const _ as u64;
@@ -56,45 +363,42 @@ impl<O: controls::Application> NewApplicationInner<O> for QtApplication {
             a.inner.set_property(common::PROPERTY.as_ptr() as *const i8, &QVariant::from_u64(selfptr));
         }
         a.set_frame_sleep(DEFAULT_FRAME_SLEEP_MS);
-        let handler = move || {
-            let mut frame_callbacks = 0;
-            while frame_callbacks < defaults::MAX_FRAME_CALLBACKS {
-                let w = &mut unsafe { &mut *(selfptr as *mut Application) }.base;
-                match w.queue().try_recv() {
-                    Ok(mut cmd) => {
-                        if (cmd.as_mut())(unsafe { &mut *(selfptr as *mut Application) }) {
-                            let _ = w.sender().send(cmd);
-                        }
-                        frame_callbacks += 1;
-                    }
-                    Err(e) => match e {
-                        mpsc::TryRecvError::Empty => break,
-                        mpsc::TryRecvError::Disconnected => unreachable!(),
-                    },
-                }
+        unsafe { a.timer.set_single_shot(true) };
+        a.queue = unsafe { SlotNoArgs::new(NullPtr, move || dispatch(selfptr as *mut Application)) };
+        a.filter = CustomEventFilter::new(move |_, event| {
+            if unsafe { event.type_() } == QEventType::from(*WAKE_EVENT) {
+                dispatch(selfptr as *mut Application);
+                true
+            } else {
+                false
             }
-        };
-        a.queue = unsafe { SlotNoArgs::new(NullPtr, handler) };
-        unsafe { a.timer.timeout().connect_with_type(ConnectionType::QueuedConnection, &a.queue) };
+        });
+        unsafe { 
+            a.timer.timeout().connect(&a.queue);
+            // only the private receiver carries the filter, a filter on the application would see every event of the process
+            a.waker.install_event_filter(a.filter.static_upcast::<QObject>());
+            *WAKE_RECEIVER.lock().unwrap() = a.waker.as_raw_ptr() as usize;
+        }
         a
     }
-}
-
-impl ApplicationInner for QtApplication {
-    fn with_name<S: AsRef<str>>(name: S) -> Box<dyn controls::Application> {
+    fn with_name_and_args(name: &str, args: QCoreApplicationArgs) -> Box<dyn controls::Application> {
         let mut b: Box<mem::MaybeUninit<Application>> = Box::new_uninit();
         let ab = AApplication::with_inner(
-            <Self as NewApplicationInner<Application>>::with_uninit_params(b.as_mut(), name.as_ref()),
+            Self::with_uninit_args(b.as_mut(), name, args),
         );
-        let mut a = unsafe {
+        let a = unsafe {
 	        b.as_mut_ptr().write(ab);
 	        b.assume_init()
         };
-        unsafe {
-        	a.inner_mut().timer.start_0a();
-        }
+        Self::wake();
         a
     }
+}
+
+impl ApplicationInner for QtApplication {
+    fn with_name<S: AsRef<str>>(name: S) -> Box<dyn controls::Application> {
+        Self::with_name_and_args(name.as_ref(), QCoreApplicationArgs::new())
+    }
 	fn frame_sleep(&self) -> u32 {
 		let interval = unsafe { self.timer.interval() };
 		if interval > -1 { interval as u32 } else { 0 }
@@ -110,16 +414,10 @@ impl ApplicationInner for QtApplication {
         }
     }
     fn start(&mut self) {
-        exit(unsafe { QApplication::exec() });
+        exit(self.run());
     }
     fn exit(&mut self) {
-        let base = &mut unsafe { common::cast_qobject_mut::<Application>(&mut self.inner) }.unwrap().base;
-        for mut window in base.windows.drain(..) {
-            window.as_any_mut().downcast_mut::<crate::window::Window>().unwrap().inner_mut().inner_mut().inner_mut().inner_mut().close(true);
-        }
-        for mut tray in base.trays.drain(..) {
-            tray.as_any_mut().downcast_mut::<crate::tray::Tray>().unwrap().inner_mut().close(true);
-        }
+        self.close_roots();
         self.maybe_exit();
     }
     fn add_root(&mut self, m: Box<dyn controls::Closeable>) -> &mut dyn controls::Member {
@@ -129,6 +427,7 @@ impl ApplicationInner for QtApplication {
         let is_tray = m.as_any().type_id() == TypeId::of::<crate::tray::Tray>();
         
         if is_window {
+            self.shortcuts.attach_window(unsafe { Ptr::from_raw(common::cast_member_to_qwidget(m.as_member()) as *const QWidget) });
             let i = base.windows.len();
             base.windows.push(m.into_any().downcast::<crate::window::Window>().unwrap());
             return base.windows[i].as_mut().as_member_mut();
@@ -282,6 +581,191 @@ impl HasNativeIdInner for QtApplication {
 }
 impl Drop for QtApplication {
     fn drop(&mut self) {
-        unsafe { QApplication::close_all_windows() };
+        // posts in progress are done once the lock is taken, events still queued are deleted along with the receiver
+        *WAKE_RECEIVER.lock().unwrap() = 0;
+        self.filter.clear();
+        unsafe { 
+            self.timer.stop();
+            QApplication::close_all_windows();
+        }
+    }
+}
+
+/// Sends the command-line arguments to the instance listening on `key`. Returns `false` if there is none.
+unsafe fn forward_to_instance(key: &QString) -> bool {
+    let socket = QLocalSocket::new_0a();
+    socket.connect_to_server_1a(key);
+    if !socket.wait_for_connected_1a(SINGLE_INSTANCE_TIMEOUT_MS) {
+        return false;
+    }
+    let args = std::env::args_os().skip(1).map(|arg| arg.to_string_lossy().into_owned()).collect::<Vec<_>>();
+    socket.write_q_byte_array(&QByteArray::from_slice(&encode_args(&args)));
+    socket.wait_for_bytes_written_1a(SINGLE_INSTANCE_TIMEOUT_MS);
+    socket.disconnect_from_server();
+    true
+}
+/// Collects what other instances send as it arrives, the arguments are complete once they disconnect.
+unsafe fn accept_instances(selfptr: *mut Application) {
+    loop {
+        let socket = match (*selfptr).inner().single_instance {
+            Some((ref server, _)) => server.next_pending_connection(),
+            None => return,
+        };
+        if socket.is_null() {
+            return;
+        }
+        let socket = socket.as_ptr();
+        let data = Rc::new(RefCell::new(Vec::new()));
+        // both slots are children of the socket and go away with it
+        let data2 = data.clone();
+        let ready_read = SlotNoArgs::new(socket, move || common::catch_panic((), || read_socket(socket, &data2)));
+        socket.ready_read().connect(&ready_read);
+        let data2 = data.clone();
+        let disconnected = SlotNoArgs::new(socket, move || common::catch_panic((), || instance_disconnected(selfptr, socket, &data2)));
+        socket.disconnected().connect(&disconnected);
+        if socket.state() == LocalSocketState::UnconnectedState {
+            instance_disconnected(selfptr, socket, &data);
+        }
+    }
+}
+unsafe fn read_socket(socket: Ptr<QLocalSocket>, data: &RefCell<Vec<u8>>) {
+    let bytes = socket.read_all();
+    data.borrow_mut().extend_from_slice(std::slice::from_raw_parts(bytes.const_data() as *const u8, bytes.count() as usize));
+}
+unsafe fn instance_disconnected(selfptr: *mut Application, socket: Ptr<QLocalSocket>, data: &RefCell<Vec<u8>>) {
+    read_socket(socket, data);
+    socket.delete_later();
+    let args = decode_args(&data.borrow());
+    if let Some(ref mut cb) = (*selfptr).inner_mut().on_another_instance {
+        let app2 = &mut *selfptr;
+        (cb.as_mut())(app2, args);
+    }
+}
+
+/// Each argument is sent as its length in bytes (`u32`, little endian) followed by its UTF-8 text, so empty arguments survive.
+fn encode_args(args: &[String]) -> Vec<u8> {
+    let mut data = Vec::new();
+    for arg in args {
+        data.extend_from_slice(&(arg.len() as u32).to_le_bytes());
+        data.extend_from_slice(arg.as_bytes());
+    }
+    data
+}
+/// Reverse of `encode_args`. A truncated trailing argument is dropped.
+fn decode_args(mut data: &[u8]) -> Vec<String> {
+    let mut args = Vec::new();
+    while data.len() >= 4 {
+        let len = u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as usize;
+        let arg = match data[4..].get(..len) {
+            Some(arg) => arg,
+            None => break,
+        };
+        args.push(String::from_utf8_lossy(arg).into_owned());
+        data = &data[4 + len..];
+    }
+    args
+}
+
+/// Drains up to `MAX_FRAME_CALLBACKS` queued commands. The frame timer is only rearmed while there is
+/// still something to run - a command asked to be repeated or the queue was not emptied - so an idle application sleeps in `exec()`.
+/// A command that panics is dropped, the rest still run.
+fn dispatch(selfptr: *mut Application) {
+    let mut frame_callbacks = 0;
+    let mut pending = false;
+    while frame_callbacks < defaults::MAX_FRAME_CALLBACKS {
+        let w = &mut unsafe { &mut *selfptr }.base;
+        match w.queue().try_recv() {
+            Ok(mut cmd) => {
+                if common::catch_panic(false, || (cmd.as_mut())(unsafe { &mut *selfptr })) {
+                    let _ = w.sender().send(cmd);
+                    pending = true;
+                }
+                frame_callbacks += 1;
+            }
+            Err(e) => match e {
+                mpsc::TryRecvError::Empty => break,
+                mpsc::TryRecvError::Disconnected => unreachable!(),
+            },
+        }
+    }
+    if frame_callbacks >= defaults::MAX_FRAME_CALLBACKS {
+        pending = true;
+    }
+    crate::executor::poll_ready(selfptr);
+    let timer = &unsafe { &*selfptr }.inner().timer;
+    unsafe {
+        if pending {
+            timer.start_0a();
+        } else {
+            timer.stop();
+        }
+    }
+}
+
+/// The running application, if called on its thread. `None` before it is created, after it is dropped and on other threads.
+pub(crate) fn application<'a>() -> Option<&'a mut Application> {
+    unsafe {
+        let app = QCoreApplication::instance();
+        if app.is_null() || app.thread().as_raw_ptr() != QThread::current_thread().as_raw_ptr() {
+            None
+        } else {
+            common::cast_qobject_mut::<Application>(&app)
+        }
+    }
+}
+
+pub(crate) fn handle_panic(payload: Box<dyn Any + Send>) {
+    let message = if let Some(message) = payload.downcast_ref::<&str>() {
+        (*message).to_owned()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Unknown panic".to_owned()
+    };
+    let action = match application() {
+        Some(app) => {
+            let app2 = app as *mut Application;
+            // taken out while running, so a panic inside the callback itself cannot recurse into it
+            match app.inner_mut().on_panic.take() {
+                Some(mut cb) => {
+                    let action = panic::catch_unwind(AssertUnwindSafe(|| (cb.as_mut())(unsafe { &mut *app2 }, message.as_str()))).unwrap_or(PanicAction::Exit);
+                    let app = unsafe { &mut *app2 }.inner_mut();
+                    if app.on_panic.is_none() {
+                        app.on_panic = Some(cb);
+                    }
+                    action
+                }
+                None => PanicAction::Exit,
+            }
+        }
+        None => PanicAction::Exit,
+    };
+    match action {
+        PanicAction::Continue => {}
+        PanicAction::ShowMessage => unsafe {
+            QMessageBox::critical_q_widget2_q_string(NullPtr, &QCoreApplication::application_name(), &QString::from_std_str(&message));
+        },
+        PanicAction::Exit => {
+            eprintln!("Exiting after a panic in a callback: {}", message);
+            exit(101);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_args, encode_args};
+
+    #[test]
+    fn args_round_trip() {
+        let args = vec!["--open".to_owned(), String::new(), "/tmp/файл с пробелом".to_owned(), String::new()];
+        assert_eq!(decode_args(&encode_args(&args)), args);
+        assert!(decode_args(&encode_args(&[])).is_empty());
+    }
+    #[test]
+    fn truncated_args() {
+        let data = encode_args(&["first".to_owned(), "second".to_owned()]);
+        assert_eq!(decode_args(&data[..data.len() - 1]), vec!["first".to_owned()]);
+        assert_eq!(decode_args(&data[..2]), Vec::<String>::new());
     }
 }
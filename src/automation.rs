@@ -0,0 +1,131 @@
+use crate::common::{self, *};
+
+use qt_core::{Key, KeyboardModifier, MouseButton};
+use qt_core::{QCoreApplication, QPoint, QPointF};
+use qt_gui::{QKeyEvent, QMouseEvent, QWheelEvent};
+use qt_widgets::{QListWidget, QTreeWidget, QTreeWidgetItem};
+
+/// Drives controls of a running application by sending them synthesized Qt input events.
+/// The events are delivered with `QCoreApplication::sendEvent`, so they pass the same event filters and signal/slot paths as real input.
+pub struct Automation<'a> {
+    app: &'a mut dyn controls::Application,
+}
+
+impl<'a> Automation<'a> {
+    pub fn new(app: &'a mut dyn controls::Application) -> Self {
+        Automation { app: app }
+    }
+    pub fn find(&mut self, tag: &str) -> Option<&mut dyn controls::Member> {
+        self.app.find_member_mut(types::FindBy::Tag(tag))
+    }
+    pub fn click(&mut self, tag: &str) -> bool {
+        self.widget(tag).map(|widget| unsafe {
+            let center = widget.rect().center();
+            mouse_click(widget, (center.x(), center.y()), MouseButton::LeftButton)
+        }).is_some()
+    }
+    pub fn click_at(&mut self, tag: &str, x: i32, y: i32, button: MouseButton) -> bool {
+        self.widget(tag).map(|widget| mouse_click(widget, (x, y), button)).is_some()
+    }
+    pub fn double_click(&mut self, tag: &str) -> bool {
+        self.widget(tag).map(|widget| unsafe {
+            let center = widget.rect().center();
+            let pos = (center.x(), center.y());
+            mouse_click(widget, pos, MouseButton::LeftButton);
+            send_mouse(widget, QEventType::MouseButtonDblClick, pos, MouseButton::LeftButton, MouseButton::LeftButton.into());
+            send_mouse(widget, QEventType::MouseButtonRelease, pos, MouseButton::LeftButton, MouseButton::NoButton.into());
+        }).is_some()
+    }
+    pub fn key_press(&mut self, tag: &str, key: Key, modifiers: QFlags<KeyboardModifier>) -> bool {
+        self.widget(tag).map(|widget| unsafe {
+            send_key(widget, QEventType::KeyPress, key.to_int(), modifiers, "");
+            send_key(widget, QEventType::KeyRelease, key.to_int(), modifiers, "");
+        }).is_some()
+    }
+    pub fn type_text(&mut self, tag: &str, text: &str) -> bool {
+        self.widget(tag).map(|widget| unsafe {
+            let mut buf = [0u8; 4];
+            for c in text.chars() {
+                let c = c.encode_utf8(&mut buf);
+                send_key(widget, QEventType::KeyPress, 0, KeyboardModifier::NoModifier.into(), c);
+                send_key(widget, QEventType::KeyRelease, 0, KeyboardModifier::NoModifier.into(), c);
+            }
+        }).is_some()
+    }
+    /// Scrolls by `delta` in eighths of a degree, positive values scroll away from the user.
+    pub fn wheel(&mut self, tag: &str, delta: i32, orientation: QOrientation) -> bool {
+        self.widget(tag).map(|widget| unsafe {
+            let center = widget.rect().center();
+            let global = widget.map_to_global(&center);
+            let event = QWheelEvent::new_6a(&QPointF::new_2a(center.x() as f64, center.y() as f64), &QPointF::new_2a(global.x() as f64, global.y() as f64), delta, MouseButton::NoButton.into(), KeyboardModifier::NoModifier.into(), orientation);
+            QCoreApplication::send_event(widget.static_upcast::<QObject>(), event.static_upcast::<QEvent>());
+        }).is_some()
+    }
+    /// Clicks the item of a `List` or `Tree` found by `indexes`, returns `false` if there is no such item.
+    pub fn click_item(&mut self, tag: &str, indexes: &[usize]) -> bool {
+        if indexes.len() < 1 {
+            return false;
+        }
+        let widget = match self.widget(tag) {
+            Some(widget) => widget,
+            None => return false,
+        };
+        unsafe {
+            let list = widget.dynamic_cast::<QListWidget>();
+            if !list.is_null() {
+                if indexes.len() != 1 {
+                    return false;
+                }
+                let item = list.item(indexes[0] as i32);
+                if item.is_null() {
+                    return false;
+                }
+                list.scroll_to_item_1a(item);
+                let center = list.visual_item_rect(item).center();
+                return mouse_click(list.viewport().as_ptr(), (center.x(), center.y()), MouseButton::LeftButton);
+            }
+            let tree = widget.dynamic_cast::<QTreeWidget>();
+            if !tree.is_null() {
+                let mut item: Ptr<QTreeWidgetItem> = tree.top_level_item(indexes[0] as i32);
+                for i in &indexes[1..] {
+                    if item.is_null() {
+                        return false;
+                    }
+                    item = item.child(*i as i32);
+                }
+                if item.is_null() {
+                    return false;
+                }
+                tree.scroll_to_item_1a(item);
+                let center = tree.visual_item_rect(item).center();
+                return mouse_click(tree.viewport().as_ptr(), (center.x(), center.y()), MouseButton::LeftButton);
+            }
+        }
+        false
+    }
+    /// Runs pending events, e.g. queued commands and deferred layout passes, without blocking.
+    pub fn process_events(&mut self) {
+        unsafe { QCoreApplication::process_events_0a() };
+    }
+
+    fn widget(&mut self, tag: &str) -> Option<Ptr<QWidget>> {
+        self.find(tag).filter(|member| member.is_control().is_some() || member.as_any().is::<crate::window::Window>()).map(|member| unsafe { Ptr::from_raw(common::cast_member_to_qwidget_mut(member)) })
+    }
+}
+
+fn mouse_click(widget: Ptr<QWidget>, pos: (i32, i32), button: MouseButton) -> bool {
+    unsafe {
+        send_mouse(widget, QEventType::MouseButtonPress, pos, button, button.into());
+        send_mouse(widget, QEventType::MouseButtonRelease, pos, button, MouseButton::NoButton.into())
+    }
+}
+unsafe fn send_mouse(widget: Ptr<QWidget>, kind: QEventType, (x, y): (i32, i32), button: MouseButton, buttons: QFlags<MouseButton>) -> bool {
+    // the screen position is given explicitly, otherwise Qt takes the current cursor position
+    let global = widget.map_to_global(&QPoint::new_2a(x, y));
+    let event = QMouseEvent::new_6a(kind, &QPointF::new_2a(x as f64, y as f64), &QPointF::new_2a(global.x() as f64, global.y() as f64), button, buttons, KeyboardModifier::NoModifier.into());
+    QCoreApplication::send_event(widget.static_upcast::<QObject>(), event.static_upcast::<QEvent>())
+}
+unsafe fn send_key(widget: Ptr<QWidget>, kind: QEventType, key: i32, modifiers: QFlags<KeyboardModifier>, text: &str) -> bool {
+    let event = QKeyEvent::new_4a(kind, key, modifiers, &QString::from_std_str(text));
+    QCoreApplication::send_event(widget.static_upcast::<QObject>(), event.static_upcast::<QEvent>())
+}
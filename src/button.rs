@@ -43,7 +43,7 @@ impl<O: controls::Button> NewButtonInner<O> for QtButton {
         let ptr = ptr as *mut _ as u64;
         let handler = move || unsafe {
             let button: &mut Button = mem::transmute(ptr);
-            if !button.inner().inner().inner().skip_callbacks {
+            if !button.inner().inner().inner().skip_callbacks && button.inner().inner().inner().base.widget.is_enabled() {
                 if let Some(ref mut cb) = button.inner_mut().inner_mut().inner_mut().h_left_clicked.0 {
                     let o: &mut O = mem::transmute(ptr);
                     (cb.as_mut())(o);
@@ -53,7 +53,7 @@ impl<O: controls::Button> NewButtonInner<O> for QtButton {
         let btn = QtButton {
             base: common::QtControlBase::with_params(unsafe { QPushButton::new() }, event_handler::<O>),
             skip_callbacks: false,
-            h_left_clicked: (None, unsafe { SlotNoArgs::new(NullPtr, handler) }),
+            h_left_clicked: (None, unsafe { SlotNoArgs::new(NullPtr, move || common::catch_panic((), &handler)) }),
         };
         unsafe {
             btn.base.widget.released().connect(&btn.h_left_clicked.1);
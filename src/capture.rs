@@ -0,0 +1,114 @@
+use crate::common::{self, *};
+
+/// Renders members to images, e.g. for visual regression tests.
+pub trait Grab {
+    /// Renders the member's widget with all its children. Returns `None` for members without a widget, like `Tray`.
+    fn grab(&self) -> Option<image::DynamicImage>;
+}
+
+impl<T: controls::Member + ?Sized> Grab for T {
+    fn grab(&self) -> Option<image::DynamicImage> {
+        let member = self.as_member();
+        if !common::is_widget_member(member) {
+            return None;
+        }
+        let widget = common::cast_member_to_qwidget(member);
+        let pixmap = unsafe { widget.grab_0a() };
+        Some(common::qimage_to_image(unsafe { pixmap.to_image() }.as_ref()))
+    }
+}
+
+/// Environment variable that makes `compare_with_golden` write `actual` as the golden image instead of comparing against it.
+pub const UPDATE_GOLDEN_ENV: &str = "PLYGUI_UPDATE_GOLDEN";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImageDiff {
+    Size { expected: (u32, u32), actual: (u32, u32) },
+    Pixels { mismatched: usize, first: (u32, u32) },
+    /// There is no golden image at the path.
+    NoGolden,
+    /// The golden image could not be read, decoded or written.
+    Io(String),
+}
+
+/// Compares two images channel by channel. A pixel matches when no RGBA channel differs by more than `tolerance`.
+pub fn compare(expected: &image::DynamicImage, actual: &image::DynamicImage, tolerance: u8) -> Result<(), ImageDiff> {
+    use image::GenericImageView;
+
+    if expected.dimensions() != actual.dimensions() {
+        return Err(ImageDiff::Size {
+            expected: expected.dimensions(),
+            actual: actual.dimensions(),
+        });
+    }
+    let expected = expected.to_rgba();
+    let actual = actual.to_rgba();
+    let mut mismatched = 0;
+    let mut first = None;
+    for (x, y, e) in expected.enumerate_pixels() {
+        let a = actual.get_pixel(x, y);
+        if e.0.iter().zip(a.0.iter()).any(|(e, a)| (*e as i16 - *a as i16).abs() > tolerance as i16) {
+            mismatched += 1;
+            first.get_or_insert((x, y));
+        }
+    }
+    match first {
+        None => Ok(()),
+        Some(first) => Err(ImageDiff::Pixels { mismatched: mismatched, first: first }),
+    }
+}
+
+/// Compares `actual` against the golden image stored at `path`. With `PLYGUI_UPDATE_GOLDEN` set to a non-empty value,
+/// `actual` is saved as the golden image instead, replacing the old one if there is any.
+pub fn compare_with_golden<P: AsRef<std::path::Path>>(path: P, actual: &image::DynamicImage, tolerance: u8) -> Result<(), ImageDiff> {
+    let path = path.as_ref();
+    if std::env::var_os(UPDATE_GOLDEN_ENV).map_or(false, |update| !update.is_empty()) {
+        return actual.save(path).map_err(|e| ImageDiff::Io(e.to_string()));
+    }
+    if !path.exists() {
+        return Err(ImageDiff::NoGolden);
+    }
+    let expected = image::open(path).map_err(|e| ImageDiff::Io(e.to_string()))?;
+    compare(&expected, actual, tolerance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compare, ImageDiff};
+    use plygui_api::external::image::{DynamicImage, Rgba, RgbaImage};
+
+    fn filled(width: u32, height: u32, color: [u8; 4]) -> DynamicImage {
+        DynamicImage::ImageRgba8(RgbaImage::from_pixel(width, height, Rgba(color)))
+    }
+
+    #[test]
+    fn equal_images_match() {
+        assert_eq!(compare(&filled(3, 2, [10, 20, 30, 255]), &filled(3, 2, [10, 20, 30, 255]), 0), Ok(()));
+    }
+    #[test]
+    fn tolerance_is_inclusive() {
+        let expected = filled(2, 2, [100, 100, 100, 255]);
+        assert_eq!(compare(&expected, &filled(2, 2, [105, 95, 100, 255]), 5), Ok(()));
+        assert_eq!(compare(&expected, &filled(2, 2, [106, 100, 100, 255]), 5), Err(ImageDiff::Pixels { mismatched: 4, first: (0, 0) }));
+    }
+    #[test]
+    fn tolerance_covers_channel_extremes() {
+        assert_eq!(compare(&filled(1, 1, [0, 0, 0, 0]), &filled(1, 1, [255, 255, 255, 255]), 255), Ok(()));
+        assert!(compare(&filled(1, 1, [0, 0, 0, 0]), &filled(1, 1, [255, 0, 0, 0]), 254).is_err());
+    }
+    #[test]
+    fn reports_first_mismatch() {
+        let expected = filled(3, 3, [0, 0, 0, 255]);
+        let mut actual = RgbaImage::from_pixel(3, 3, Rgba([0, 0, 0, 255]));
+        actual.put_pixel(2, 1, Rgba([0, 0, 0, 0]));
+        actual.put_pixel(0, 2, Rgba([0, 0, 0, 0]));
+        assert_eq!(compare(&expected, &DynamicImage::ImageRgba8(actual), 0), Err(ImageDiff::Pixels { mismatched: 2, first: (2, 1) }));
+    }
+    #[test]
+    fn size_mismatch() {
+        assert_eq!(
+            compare(&filled(2, 3, [0, 0, 0, 255]), &filled(3, 2, [0, 0, 0, 255]), 255),
+            Err(ImageDiff::Size { expected: (2, 3), actual: (3, 2) })
+        );
+    }
+}
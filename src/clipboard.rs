@@ -0,0 +1,152 @@
+use crate::common::{self, *};
+
+use qt_core::{QMimeData, SlotNoArgs};
+use qt_gui::q_clipboard::Mode as QClipboardMode;
+use qt_gui::{QClipboard, QGuiApplication};
+
+pub type OnClipboardChanged = Box<dyn FnMut(ClipboardMode)>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClipboardMode {
+    Clipboard,
+    /// The X11 primary selection, filled by selecting text and pasted with the middle mouse button.
+    Selection,
+}
+
+/// Access to the system clipboard. Writes replace the whole content of the chosen clipboard.
+pub struct Clipboard {
+    on_changed: Option<OnClipboardChanged>,
+    h_changed: QBox<SlotNoArgs>,
+    h_selection_changed: QBox<SlotNoArgs>,
+}
+
+impl Clipboard {
+    pub(crate) fn new() -> Box<Self> {
+        let mut c = Box::new(Clipboard {
+            on_changed: None,
+            h_changed: unsafe { SlotNoArgs::new(NullPtr, move || {}) },
+            h_selection_changed: unsafe { SlotNoArgs::new(NullPtr, move || {}) },
+        });
+        let ptr = c.as_mut() as *mut Clipboard;
+        unsafe {
+            c.h_changed.set(move || common::catch_panic((), || (&mut *ptr).changed(ClipboardMode::Clipboard)));
+            c.h_selection_changed.set(move || common::catch_panic((), || (&mut *ptr).changed(ClipboardMode::Selection)));
+            let clipboard = clipboard();
+            clipboard.data_changed().connect(&c.h_changed);
+            clipboard.selection_changed().connect(&c.h_selection_changed);
+        }
+        c
+    }
+    /// Whether the platform has a separate selection clipboard, which is usually X11 only.
+    pub fn supports_selection(&self) -> bool {
+        unsafe { clipboard().supports_selection() }
+    }
+    pub fn on_changed(&mut self, cb: Option<OnClipboardChanged>) {
+        self.on_changed = cb;
+    }
+    pub fn clear(&mut self, mode: ClipboardMode) {
+        unsafe { clipboard().clear_1a(mode_to_qmode(mode)) };
+    }
+
+    pub fn text(&self, mode: ClipboardMode) -> Option<String> {
+        unsafe {
+            let data = clipboard().mime_data_1a(mode_to_qmode(mode));
+            if data.is_null() || !data.has_text() {
+                None
+            } else {
+                Some(data.text().to_std_string())
+            }
+        }
+    }
+    pub fn set_text(&mut self, mode: ClipboardMode, text: &str) {
+        unsafe { clipboard().set_text_2a(&QString::from_std_str(text), mode_to_qmode(mode)) };
+    }
+    pub fn html(&self, mode: ClipboardMode) -> Option<String> {
+        unsafe {
+            let data = clipboard().mime_data_1a(mode_to_qmode(mode));
+            if data.is_null() || !data.has_html() {
+                None
+            } else {
+                Some(data.html().to_std_string())
+            }
+        }
+    }
+    /// Puts HTML on the clipboard, with `alt_text` as the plain text version for targets that cannot paste HTML.
+    pub fn set_html(&mut self, mode: ClipboardMode, html: &str, alt_text: &str) {
+        unsafe {
+            let data = QMimeData::new();
+            data.set_html(&QString::from_std_str(html));
+            data.set_text(&QString::from_std_str(alt_text));
+            clipboard().set_mime_data_2a(data.into_ptr(), mode_to_qmode(mode));
+        }
+    }
+    pub fn image(&self, mode: ClipboardMode) -> Option<image::DynamicImage> {
+        unsafe {
+            let image = clipboard().image_1a(mode_to_qmode(mode));
+            if image.is_null() {
+                None
+            } else {
+                Some(common::qimage_to_image(image.as_ref()))
+            }
+        }
+    }
+    pub fn set_image(&mut self, mode: ClipboardMode, image: &image::DynamicImage) {
+        use image::GenericImageView;
+
+        let (w, h) = image.dimensions();
+        let mut raw = image.to_rgba().into_raw();
+        unsafe {
+            // the QImage only borrows the buffer, so keep a deep copy on the clipboard
+            let img = QImage::from_uchar2_int_format(raw.as_mut_ptr(), w as i32, h as i32, Format::FormatRGBA8888);
+            clipboard().set_image_2a(&img.copy_0a(), mode_to_qmode(mode));
+        }
+    }
+    pub fn formats(&self, mode: ClipboardMode) -> Vec<String> {
+        unsafe {
+            let data = clipboard().mime_data_1a(mode_to_qmode(mode));
+            if data.is_null() {
+                Vec::new()
+            } else {
+                common::qstring_list_to_vec(&data.formats())
+            }
+        }
+    }
+    pub fn data(&self, mode: ClipboardMode, mime: &str) -> Option<Vec<u8>> {
+        let mime = QString::from_std_str(mime);
+        unsafe {
+            let data = clipboard().mime_data_1a(mode_to_qmode(mode));
+            if data.is_null() || !data.has_format(&mime) {
+                None
+            } else {
+                let bytes = data.data(&mime);
+                Some(std::slice::from_raw_parts(bytes.const_data() as *const u8, bytes.count() as usize).to_vec())
+            }
+        }
+    }
+    /// Puts several representations of the same content on the clipboard at once, e.g. `[("text/plain", ...), ("application/x-myapp", ...)]`.
+    pub fn set_data(&mut self, mode: ClipboardMode, payloads: &[(&str, &[u8])]) {
+        unsafe {
+            let data = QMimeData::new();
+            for (mime, payload) in payloads {
+                data.set_data(&QString::from_std_str(mime), &QByteArray::from_slice(payload));
+            }
+            clipboard().set_mime_data_2a(data.into_ptr(), mode_to_qmode(mode));
+        }
+    }
+
+    fn changed(&mut self, mode: ClipboardMode) {
+        if let Some(ref mut cb) = self.on_changed {
+            (cb.as_mut())(mode);
+        }
+    }
+}
+
+unsafe fn clipboard() -> QPtr<QClipboard> {
+    QGuiApplication::clipboard()
+}
+fn mode_to_qmode(mode: ClipboardMode) -> QClipboardMode {
+    match mode {
+        ClipboardMode::Clipboard => QClipboardMode::Clipboard,
+        ClipboardMode::Selection => QClipboardMode::Selection,
+    }
+}
@@ -0,0 +1,106 @@
+use crate::common::*;
+
+use qt_core::{QCommandLineOption, QCommandLineParser, QCoreApplication};
+
+use std::str::FromStr;
+
+/// Command-line options and positional arguments of the application, parsed with `QCommandLineParser`.
+/// Standard Qt arguments like `-platform` or `-style` are consumed by `QApplication` before the parser sees them.
+pub struct CommandLine {
+    parser: CppBox<QCommandLineParser>,
+    options: Vec<CppBox<QCommandLineOption>>,
+    processed: bool,
+}
+
+impl CommandLine {
+    pub(crate) fn new() -> Self {
+        let parser = unsafe { QCommandLineParser::new() };
+        unsafe {
+            parser.add_help_option();
+            parser.add_version_option();
+        }
+        CommandLine {
+            parser: parser,
+            options: Vec::new(),
+            processed: false,
+        }
+    }
+    pub fn set_description(&mut self, description: &str) {
+        unsafe { self.parser.set_application_description(&QString::from_std_str(description)) };
+    }
+    /// Sets the version printed by `--version`.
+    pub fn set_version(&mut self, version: &str) {
+        unsafe { QCoreApplication::set_application_version(&QString::from_std_str(version)) };
+    }
+    /// Declares a boolean switch, e.g. `&["v", "verbose"]`. Returns `false` if any of the names is already taken.
+    pub fn add_flag(&mut self, names: &[&str], description: &str) -> bool {
+        self.add_option_inner(names, description, None, None)
+    }
+    /// Declares an option that takes a value, with an optional default used when it is absent.
+    pub fn add_option(&mut self, names: &[&str], description: &str, value_name: &str, default: Option<&str>) -> bool {
+        self.add_option_inner(names, description, Some(value_name), default)
+    }
+    pub fn add_positional(&mut self, name: &str, description: &str) {
+        unsafe { self.parser.add_positional_argument_2a(&QString::from_std_str(name), &QString::from_std_str(description)) };
+    }
+    /// Parses the application arguments. Prints help or version and exits the process on `--help` or `--version`, and prints an error and exits on unknown options.
+    /// Getters call it implicitly, so it only needs to be called explicitly to validate the arguments early.
+    pub fn process(&mut self) {
+        if !self.processed {
+            self.processed = true;
+            unsafe { self.parser.process_q_core_application(QCoreApplication::instance().as_ref().unwrap()) };
+        }
+    }
+    pub fn is_set(&mut self, name: &str) -> bool {
+        self.process();
+        unsafe { self.parser.is_set_q_string(&QString::from_std_str(name)) }
+    }
+    /// Returns the last value of the option, or its default value if the option was not given.
+    pub fn value(&mut self, name: &str) -> Option<String> {
+        self.process();
+        let name = QString::from_std_str(name);
+        unsafe {
+            let values = self.parser.values_q_string(&name);
+            if values.is_empty() {
+                None
+            } else {
+                Some(values.last().to_std_string())
+            }
+        }
+    }
+    pub fn values(&mut self, name: &str) -> Vec<String> {
+        self.process();
+        unsafe { qstring_list_to_vec(&self.parser.values_q_string(&QString::from_std_str(name))) }
+    }
+    /// Parses the value of the option into `T`. Returns `None` if the option was not given and has no default.
+    pub fn get<T: FromStr>(&mut self, name: &str) -> Option<Result<T, T::Err>> {
+        self.value(name).map(|value| value.parse())
+    }
+    pub fn positional(&mut self) -> Vec<String> {
+        self.process();
+        unsafe { qstring_list_to_vec(&self.parser.positional_arguments()) }
+    }
+    pub fn help_text(&self) -> String {
+        unsafe { self.parser.help_text().to_std_string() }
+    }
+
+    fn add_option_inner(&mut self, names: &[&str], description: &str, value_name: Option<&str>, default: Option<&str>) -> bool {
+        unsafe {
+            let qnames = QStringList::new();
+            for name in names {
+                qnames.append_q_string(&QString::from_std_str(name));
+            }
+            let option = QCommandLineOption::from_q_string_list_q_string_q_string_q_string(
+                &qnames,
+                &QString::from_std_str(description),
+                &QString::from_std_str(value_name.unwrap_or("")),
+                &QString::from_std_str(default.unwrap_or("")),
+            );
+            let added = self.parser.add_option(&option);
+            if added {
+                self.options.push(option);
+            }
+            added
+        }
+    }
+}
@@ -7,6 +7,7 @@ pub use qt_core::Orientation as QOrientation;
 pub use qt_core::QSize;
 pub use qt_core::SlotNoArgs;
 pub use qt_core::QString;
+pub use qt_core::QStringList;
 pub use qt_core::QVariant;
 pub use qt_core::QBox;
 pub use qt_core::QPtr;
@@ -27,9 +28,33 @@ pub use plygui_api::sdk::*;
 pub use plygui_api::{callbacks, controls, defaults, ids, layout, types::{self, adapter}, utils};
 pub use plygui_api::external::image;
 
+use qt_widgets::{QAction, QMainWindow, QSystemTrayIcon};
+
 lazy_static! {
     pub static ref PROPERTY: CString = CString::new("plygui").unwrap();
     pub static ref PROPERTY_PARENT: CString = CString::new("plygui_parent").unwrap();
+    pub static ref PROPERTY_EVENTS: CString = CString::new("plygui_events").unwrap();
+}
+
+pub type OnMemberEvent = Box<dyn FnMut(&mut dyn controls::Member)>;
+
+/// Callbacks the Qt backend offers on top of plygui-api. Owned by the member and found through `PROPERTY_EVENTS` of its native object,
+/// so they can be set on any member regardless of its type.
+#[derive(Default)]
+pub struct EventCallbacks {
+    pub on_palette_changed: Option<OnMemberEvent>,
+    pub(crate) context_menu: Option<Box<crate::context_menu::ContextMenu>>,
+    pub(crate) mouse: crate::mouse::MouseCallbacks,
+    pub(crate) keyboard: crate::keyboard::KeyboardCallbacks,
+    pub(crate) window: crate::window_state::WindowCallbacks,
+}
+
+impl EventCallbacks {
+    pub fn attached_to(object: &QObject) -> Box<EventCallbacks> {
+        let events: Box<EventCallbacks> = Box::new(Default::default());
+        unsafe { object.set_property(PROPERTY_EVENTS.as_ptr() as *const i8, &QVariant::from_u64(events.as_ref() as *const _ as u64)) };
+        events
+    }
 }
 
 pub enum MaybeCppBox<T: CppDeletable> {
@@ -115,18 +140,21 @@ pub struct QtControlBase<T: controls::Control + Sized, Q: StaticUpcast<QWidget>
     pub dirty: bool,
 
     event_callback: QBox<CustomEventFilter>,
+    events: Box<EventCallbacks>,
 
     _marker: marker::PhantomData<T>,
 }
 
 impl<T: controls::Control + Sized, Q: StaticUpcast<QWidget> + StaticUpcast<QObject> + CppDeletable> QtControlBase<T, Q> {
-    pub fn with_params<F>(widget: QBox<Q>, event_callback: F) -> QtControlBase<T, Q>
+    pub fn with_params<F>(widget: QBox<Q>, mut event_callback: F) -> QtControlBase<T, Q>
     where
-        F: for<'a, 'b> FnMut(&'a mut QObject, &'b mut QEvent) -> bool,
+        F: for<'a, 'b> FnMut(&'a mut QObject, &'b mut QEvent) -> bool + 'static,
     {
+        let events = EventCallbacks::attached_to(unsafe { &widget.static_upcast::<QObject>() });
         let base = QtControlBase {
             widget: widget,
-            event_callback: CustomEventFilter::new(event_callback),
+            event_callback: CustomEventFilter::new(move |object, event| catch_panic(false, || dispatch_events(object, event) || event_callback(object, event))),
+            events: events,
             dirty: true,
             _marker: marker::PhantomData,
         };
@@ -138,10 +166,14 @@ impl<T: controls::Control + Sized, Q: StaticUpcast<QWidget> + StaticUpcast<QObje
         }
         base
     }
+    pub fn events_mut(&mut self) -> &mut EventCallbacks {
+        &mut self.events
+    }
     pub fn as_qwidget(&self) -> QPtr<QWidget> {
         unsafe { self.widget.static_upcast::<QWidget>() }
     }
-    pub fn draw(&mut self, _member: &mut MemberBase, control: &mut ControlBase) {
+    pub fn draw(&mut self, member: &mut MemberBase, control: &mut ControlBase) {
+        crate::style::sync_object_name(member.as_member());
         if let Some(_coords) = control.coords {
             //self.widget.static_upcast::<QWidget>().move_((coords.0 as i32, coords.1 as i32));
             let wpolicy = match control.layout.width {
@@ -251,6 +283,56 @@ impl<T: controls::Control + Sized, Q: StaticUpcast<QWidget> + StaticUpcast<QObje
     }
 }
 
+/// Runs `f`, which calls user code from a Qt slot or event filter, and hands a panic over to the application instead of unwinding into C++.
+pub fn catch_panic<R, F: FnOnce() -> R>(default: R, f: F) -> R {
+    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)) {
+        Ok(result) => result,
+        Err(payload) => {
+            crate::application::handle_panic(payload);
+            default
+        }
+    }
+}
+pub fn event_callbacks_mut<'a>(object: &QObject) -> Option<&'a mut EventCallbacks> {
+    unsafe {
+        let qv = object.property(PROPERTY_EVENTS.as_ptr() as *const i8);
+        if !qv.is_valid() || qv.to_u_long_long_0a() == 0 {
+            None
+        } else {
+            Some(&mut *(qv.to_u_long_long_0a() as usize as *mut EventCallbacks))
+        }
+    }
+}
+pub fn member_event_callbacks_mut(member: &mut dyn controls::Member) -> Option<&mut EventCallbacks> {
+    event_callbacks_mut(unsafe { &*(member.native_id() as *const QObject) })
+}
+/// Runs the `EventCallbacks` of the object the event was sent to. Returns `true` if a callback consumed the event.
+pub fn dispatch_events(object: &mut QObject, event: &mut QEvent) -> bool {
+    let events = match event_callbacks_mut(object) {
+        Some(events) => events,
+        None => return false,
+    };
+    match unsafe { event.type_() } {
+        QEventType::PaletteChange => {
+            if let Some(ref mut cb) = events.on_palette_changed {
+                if let Some(member) = cast_qobject_to_base_mut(object) {
+                    (cb.as_mut())(member.as_member_mut());
+                }
+            }
+        }
+        QEventType::MouseButtonPress
+        | QEventType::MouseButtonRelease
+        | QEventType::MouseButtonDblClick
+        | QEventType::MouseMove
+        | QEventType::Enter
+        | QEventType::Leave
+        | QEventType::Wheel => return crate::mouse::dispatch(object, event),
+        QEventType::KeyPress | QEventType::KeyRelease | QEventType::FocusIn | QEventType::FocusOut => return crate::keyboard::dispatch(object, event),
+        QEventType::WindowStateChange | QEventType::Move | QEventType::Resize | QEventType::Close => crate::window_state::dispatch(object, event),
+        _ => {}
+    }
+    false
+}
 pub fn cast_control_to_qwidget_mut(control: &mut dyn controls::Control) -> &mut QWidget {
     cast_member_to_qwidget_mut(control.as_member_mut())
 }
@@ -325,18 +407,109 @@ pub fn qorientation_to_orientation(o: QOrientation) -> layout::Orientation {
     let raw = src.to_rgba().into_raw();
     unsafe { QImage::new_unsafe((raw.as_ptr(), w as i32, h as i32, Format::FormatRGBA8888)) }
 }*/
-pub fn append_item<T: controls::Member>(menu: &QMenu, label: String, action: callbacks::Action, storage: &mut Vec<(callbacks::Action, QBox<SlotNoArgs>)>, slot_spawn: fn(id: usize, selfptr: *mut T) -> QBox<SlotNoArgs>, selfptr: *mut T) {
+pub fn qimage_to_image(src: &QImage) -> image::DynamicImage {
+    unsafe {
+        let src = src.convert_to_format_1a(Format::FormatRGBA8888);
+        let (w, h) = (cmp::max(0, src.width()) as usize, cmp::max(0, src.height()) as usize);
+        let stride = src.bytes_per_line() as usize;
+        let bits = src.const_bits();
+        let mut raw = Vec::with_capacity(w * h * 4);
+        for y in 0..h {
+            raw.extend_from_slice(std::slice::from_raw_parts(bits.add(y * stride), w * 4));
+        }
+        image::DynamicImage::ImageRgba8(image::RgbaImage::from_raw(w as u32, h as u32, raw).unwrap())
+    }
+}
+/// Calls `f` for the member and then, depth first, for every child of its single or multi container.
+pub fn for_each_member_mut(member: &mut dyn controls::Member, f: &mut dyn FnMut(&mut dyn controls::Member)) {
+    f(member);
+    if let Some(container) = member.is_container_mut() {
+        if let Some(single) = container.is_single_container_mut() {
+            if let Some(child) = single.child_mut() {
+                for_each_member_mut(child.as_member_mut(), f);
+            }
+        } else if let Some(multi) = container.is_multi_container_mut() {
+            for i in 0..multi.len() {
+                if let Some(child) = multi.child_at_mut(i) {
+                    for_each_member_mut(child.as_member_mut(), f);
+                }
+            }
+        }
+    }
+}
+/// Whether the native id of the member points to a `QWidget`. Trays are backed by a `QSystemTrayIcon`, which is not one.
+pub fn is_widget_member(member: &dyn controls::Member) -> bool {
+    !member.as_any().is::<crate::tray::Tray>()
+}
+pub unsafe fn qstring_list_to_vec(list: &QStringList) -> Vec<String> {
+    (0..list.size()).map(|i| list.at(i).to_std_string()).collect()
+}
+/// The action at `path` in the window menu, the tray menu or the context menu set with `set_context_menu` of the member.
+pub fn menu_action(member: &dyn controls::Member, path: &[usize]) -> Option<Ptr<QAction>> {
+    let root = menu_root(member)?;
+    let (first, rest) = path.split_first()?;
+    unsafe {
+        let mut action = nth_action(root, *first)?;
+        for i in rest {
+            let submenu = action.menu();
+            if submenu.is_null() {
+                return None;
+            }
+            action = nth_action(submenu.static_upcast::<QWidget>().as_ptr(), *i)?;
+        }
+        Some(action)
+    }
+}
+fn menu_root(member: &dyn controls::Member) -> Option<Ptr<QWidget>> {
+    unsafe {
+        let object = Ptr::from_raw(member.native_id() as *const QObject);
+        let window = object.dynamic_cast::<QMainWindow>();
+        if !window.is_null() {
+            return Some(window.menu_bar().static_upcast::<QWidget>().as_ptr());
+        }
+        let tray = object.dynamic_cast::<QSystemTrayIcon>();
+        if !tray.is_null() {
+            let menu = tray.context_menu();
+            return if menu.is_null() { None } else { Some(menu.static_upcast::<QWidget>().as_ptr()) };
+        }
+        event_callbacks_mut(&object)
+            .and_then(|events| events.context_menu.as_ref())
+            .and_then(|context_menu| context_menu.built_menu())
+            .map(|menu| menu.static_upcast::<QWidget>())
+    }
+}
+unsafe fn nth_action(widget: Ptr<QWidget>, i: usize) -> Option<Ptr<QAction>> {
+    let actions = widget.actions();
+    if i < actions.size() as usize {
+        Some(actions.value_1a(i as i32))
+    } else {
+        None
+    }
+}
+/// Shows `shortcut` next to the label of a menu item, in the column Qt menus keep for shortcuts. The item is not triggered by it.
+pub fn set_action_shortcut_text(action: Ptr<QAction>, shortcut: Option<&str>) {
+    unsafe {
+        let text = action.text().to_std_string();
+        let label = text.split('\t').next().unwrap_or("");
+        let text = match shortcut {
+            Some(shortcut) => format!("{}\t{}", label, shortcut),
+            None => label.to_owned(),
+        };
+        action.set_text(&QString::from_std_str(text));
+    }
+}
+pub fn append_item<T>(menu: &QMenu, label: String, action: callbacks::Action, storage: &mut Vec<(callbacks::Action, QBox<SlotNoArgs>)>, slot_spawn: fn(id: usize, selfptr: *mut T) -> QBox<SlotNoArgs>, selfptr: *mut T) {
     let id = storage.len();
     let action = (action, slot_spawn(id, selfptr));
     let qaction = unsafe { &*menu.add_action_q_string(QString::from_std_str(label).as_ref()) };
     unsafe { qaction.triggered().connect(&action.1) }; 
     storage.push(action);
 }
-pub fn append_level<T: controls::Member>(menu: &QMenu, label: String, items: Vec<types::MenuItem>, storage: &mut Vec<(callbacks::Action, QBox<SlotNoArgs>)>, slot_spawn: fn(id: usize, selfptr: *mut T) -> QBox<SlotNoArgs>, selfptr: *mut T) {
+pub fn append_level<T>(menu: &QMenu, label: String, items: Vec<types::MenuItem>, storage: &mut Vec<(callbacks::Action, QBox<SlotNoArgs>)>, slot_spawn: fn(id: usize, selfptr: *mut T) -> QBox<SlotNoArgs>, selfptr: *mut T) {
     let submenu = unsafe { menu.add_menu_q_string(QString::from_std_str(label).as_ref()) };
     make_menu(&submenu, items, storage, slot_spawn, selfptr);
 }
-pub fn make_menu<T: controls::Member>(menu: &QMenu, mut items: Vec<types::MenuItem>, storage: &mut Vec<(callbacks::Action, QBox<SlotNoArgs>)>, slot_spawn: fn(id: usize, selfptr: *mut T) -> QBox<SlotNoArgs>, selfptr: *mut T) {
+pub fn make_menu<T>(menu: &QMenu, mut items: Vec<types::MenuItem>, storage: &mut Vec<(callbacks::Action, QBox<SlotNoArgs>)>, slot_spawn: fn(id: usize, selfptr: *mut T) -> QBox<SlotNoArgs>, selfptr: *mut T) {
     let mut options = Vec::new();
     let mut help = Vec::new();
 
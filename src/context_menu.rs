@@ -0,0 +1,236 @@
+use crate::common::{self, *};
+
+use qt_core::{ContextMenuPolicy, QPoint, SlotOfQPoint};
+use qt_widgets::{QAbstractScrollArea, QListWidget, QTreeWidget};
+
+pub type OnContextMenu = Box<dyn FnMut(&mut dyn controls::Member) -> types::Menu>;
+pub type OnItemContextMenu = Box<dyn FnMut(&mut dyn controls::Member, &[usize]) -> types::Menu>;
+
+enum MenuSource {
+    Built,
+    Lazy(OnContextMenu),
+    Item(OnItemContextMenu),
+    // the callback is taken out while it runs
+    Running,
+}
+
+/// Context menu of a member, kept in its `EventCallbacks`. Menu actions live in the same slot storage `common::make_menu` fills for window menus.
+pub struct ContextMenu {
+    source: MenuSource,
+    menu: Option<QBox<QMenu>>,
+    storage: Vec<(callbacks::Action, QBox<SlotNoArgs>)>,
+    // deleted later, it may be the slot currently running
+    requested: Option<QBox<SlotOfQPoint>>,
+}
+
+/// Context menus shown at the cursor on right-click or the menu key.
+pub trait HasContextMenu {
+    /// Shows `menu` as the context menu. `None` removes it.
+    fn set_context_menu(&mut self, menu: types::Menu);
+    /// Builds the context menu each time it is requested. The callback may return `None` to show nothing.
+    fn on_context_menu(&mut self, cb: Option<OnContextMenu>);
+}
+
+impl<T: controls::Control + ?Sized> HasContextMenu for T {
+    fn set_context_menu(&mut self, menu: types::Menu) {
+        match menu {
+            Some(items) => {
+                let object = self.native_id() as *mut QObject;
+                let mut context_menu = attach(self.as_member_mut(), MenuSource::Built);
+                context_menu.build(items, object);
+                set(self.as_member_mut(), Some(context_menu));
+            }
+            None => set(self.as_member_mut(), None),
+        }
+    }
+    fn on_context_menu(&mut self, cb: Option<OnContextMenu>) {
+        let context_menu = cb.map(|cb| attach(self.as_member_mut(), MenuSource::Lazy(cb)));
+        set(self.as_member_mut(), context_menu);
+    }
+}
+
+/// Context menus of adapter items in `List` and `Tree`. The callback gets the index path of the item under the cursor,
+/// `[i]` for a list row and `[i, j, ...]` for a tree node. Nothing is shown outside of the items.
+pub trait HasItemContextMenu {
+    fn on_item_context_menu(&mut self, cb: Option<OnItemContextMenu>);
+}
+
+impl HasItemContextMenu for dyn controls::List {
+    fn on_item_context_menu(&mut self, cb: Option<OnItemContextMenu>) {
+        let context_menu = cb.map(|cb| attach(self.as_member_mut(), MenuSource::Item(cb)));
+        set(self.as_member_mut(), context_menu);
+    }
+}
+impl HasItemContextMenu for dyn controls::Tree {
+    fn on_item_context_menu(&mut self, cb: Option<OnItemContextMenu>) {
+        let context_menu = cb.map(|cb| attach(self.as_member_mut(), MenuSource::Item(cb)));
+        set(self.as_member_mut(), context_menu);
+    }
+}
+
+impl ContextMenu {
+    /// The menu set with `set_context_menu`. Menus built by callbacks are rebuilt each time they are shown, so they have none.
+    pub(crate) fn built_menu(&self) -> Option<Ptr<QMenu>> {
+        match self.source {
+            MenuSource::Built => self.menu.as_ref().map(|menu| unsafe { menu.as_ptr() }),
+            _ => None,
+        }
+    }
+    fn is_running(&self) -> bool {
+        match self.source {
+            MenuSource::Running => true,
+            _ => false,
+        }
+    }
+    fn build(&mut self, items: Vec<types::MenuItem>, object: *mut QObject) {
+        self.discard_menu();
+        let menu = unsafe { QMenu::new() };
+        common::make_menu(&menu, items, &mut self.storage, slot_spawn, object);
+        self.menu = Some(menu);
+    }
+    // an action of the old menu may be the one currently running
+    fn discard_menu(&mut self) {
+        unsafe {
+            if let Some(menu) = self.menu.take() {
+                menu.into_ptr().delete_later();
+            }
+            for (_, slot) in self.storage.drain(..) {
+                slot.into_ptr().delete_later();
+            }
+        }
+    }
+}
+impl Drop for ContextMenu {
+    fn drop(&mut self) {
+        self.discard_menu();
+        if let Some(requested) = self.requested.take() {
+            unsafe { requested.into_ptr().delete_later() };
+        }
+    }
+}
+
+fn attach(member: &mut dyn controls::Member, source: MenuSource) -> Box<ContextMenu> {
+    let object = member.native_id() as *mut QObject;
+    let requested = unsafe { SlotOfQPoint::new(NullPtr, move |pos| common::catch_panic((), || requested(object, pos))) };
+    unsafe {
+        let widget = common::cast_member_to_qwidget(member);
+        widget.set_context_menu_policy(ContextMenuPolicy::CustomContextMenu);
+        widget.custom_context_menu_requested().connect(&requested);
+    }
+    Box::new(ContextMenu {
+        source: source,
+        menu: None,
+        storage: Vec::new(),
+        requested: Some(requested),
+    })
+}
+fn set(member: &mut dyn controls::Member, context_menu: Option<Box<ContextMenu>>) {
+    if context_menu.is_none() {
+        unsafe { common::cast_member_to_qwidget(member).set_context_menu_policy(ContextMenuPolicy::DefaultContextMenu) };
+    }
+    if let Some(events) = common::member_event_callbacks_mut(member) {
+        events.context_menu = context_menu;
+    }
+}
+
+fn requested(object: *mut QObject, pos: Ref<QPoint>) {
+    let object = unsafe { &mut *object };
+    let member = match common::cast_qobject_to_base_mut(object) {
+        Some(base) => base.as_member_mut(),
+        None => return,
+    };
+    let context_menu = match common::event_callbacks_mut(object).and_then(|events| events.context_menu.as_mut()) {
+        Some(context_menu) => context_menu,
+        None => return,
+    };
+    // taken out while running, so the callback may replace or remove the context menu it belongs to
+    let mut source = mem::replace(&mut context_menu.source, MenuSource::Running);
+    let items = match source {
+        MenuSource::Built | MenuSource::Running => None,
+        MenuSource::Lazy(ref mut cb) => (cb.as_mut())(member),
+        MenuSource::Item(ref mut cb) => item_path(object, pos).and_then(|indexes| (cb.as_mut())(member, indexes.as_slice())),
+    };
+    let built = match source {
+        MenuSource::Built => true,
+        _ => false,
+    };
+    // a context menu set meanwhile is a new one, which never has a running source
+    let context_menu = match common::event_callbacks_mut(object).and_then(|events| events.context_menu.as_mut()) {
+        Some(context_menu) if context_menu.is_running() => context_menu,
+        _ => return,
+    };
+    context_menu.source = source;
+    match items {
+        Some(items) => context_menu.build(items, object),
+        None if !built => return,
+        None => {}
+    }
+    if let Some(ref menu) = context_menu.menu {
+        unsafe {
+            // item views report the position relative to their viewport
+            let object = Ptr::from_raw(object as *const QObject);
+            let area = object.dynamic_cast::<QAbstractScrollArea>();
+            let global = if area.is_null() {
+                object.dynamic_cast::<QWidget>().map_to_global(pos)
+            } else {
+                area.viewport().map_to_global(pos)
+            };
+            menu.popup_1a(&global);
+        }
+    }
+}
+fn slot_spawn(id: usize, object: *mut QObject) -> QBox<SlotNoArgs> {
+    let handler = move || {
+        let object = unsafe { &mut *object };
+        let member = match common::cast_qobject_to_base_mut(object) {
+            Some(base) => base.as_member_mut(),
+            None => return,
+        };
+        let context_menu = match common::event_callbacks_mut(object).and_then(|events| events.context_menu.as_mut()) {
+            Some(context_menu) => context_menu,
+            None => return,
+        };
+        let menu = context_menu.menu.as_ref().map(|menu| unsafe { menu.as_raw_ptr() });
+        // taken out while running, so the action may replace or remove the menu it belongs to
+        let mut action = match context_menu.storage.get_mut(id) {
+            Some((a, _)) => mem::replace(a, (|_: &mut dyn controls::Member| false).into()),
+            None => return,
+        };
+        (action.as_mut())(member);
+        if let Some(context_menu) = common::event_callbacks_mut(object).and_then(|events| events.context_menu.as_mut()) {
+            // a rebuilt menu is a new object, the old one is only deleted later
+            if context_menu.menu.as_ref().map(|menu| unsafe { menu.as_raw_ptr() }) == menu {
+                if let Some((a, _)) = context_menu.storage.get_mut(id) {
+                    *a = action;
+                }
+            }
+        }
+    };
+    unsafe { SlotNoArgs::new(NullPtr, move || common::catch_panic((), &handler)) }
+}
+
+fn item_path(object: &QObject, pos: Ref<QPoint>) -> Option<Vec<usize>> {
+    unsafe {
+        let object = Ptr::from_raw(object as *const QObject);
+        let list = object.dynamic_cast::<QListWidget>();
+        if !list.is_null() {
+            let item = list.item_at_q_point(pos);
+            return if item.is_null() { None } else { Some(vec![list.row(item) as usize]) };
+        }
+        let tree = object.dynamic_cast::<QTreeWidget>();
+        if tree.is_null() {
+            return None;
+        }
+        let mut item = tree.item_at_q_point(pos);
+        if item.is_null() {
+            return None;
+        }
+        let mut indexes = Vec::new();
+        while !item.parent().is_null() {
+            indexes.insert(0, item.parent().index_of_child(item) as usize);
+            item = item.parent();
+        }
+        indexes.insert(0, tree.index_of_top_level_item(item) as usize);
+        Some(indexes)
+    }
+}
@@ -0,0 +1,41 @@
+use crate::common::{self, *};
+
+/// Enabled state of controls. Disabling a container disables its whole subtree, and `is_enabled` reports the inherited state,
+/// the way Qt does. Disabled controls do not fire their click callbacks.
+pub trait HasEnabled {
+    fn set_enabled(&mut self, enabled: bool);
+    fn is_enabled(&self) -> bool;
+}
+
+impl<T: controls::Control + ?Sized> HasEnabled for T {
+    fn set_enabled(&mut self, enabled: bool) {
+        unsafe { common::cast_member_to_qwidget_mut(self.as_member_mut()).set_enabled(enabled) };
+    }
+    fn is_enabled(&self) -> bool {
+        unsafe { common::cast_member_to_qwidget(self.as_member()).is_enabled() }
+    }
+}
+
+/// Enabled state of the items of a window menu, a tray menu or a context menu set with `set_context_menu`.
+/// Items are addressed by their path in the shown menu, where `Options` and `Help` role items come after all others and delimiters count as items.
+/// Disabled items do not fire their actions.
+pub trait MenuItemsEnabled {
+    /// Returns `false` if there is no item at `path`.
+    fn set_menu_item_enabled(&mut self, path: &[usize], enabled: bool) -> bool;
+    fn is_menu_item_enabled(&self, path: &[usize]) -> Option<bool>;
+}
+
+impl<T: controls::Member + ?Sized> MenuItemsEnabled for T {
+    fn set_menu_item_enabled(&mut self, path: &[usize], enabled: bool) -> bool {
+        match common::menu_action(self.as_member(), path) {
+            Some(action) => {
+                unsafe { action.set_enabled(enabled) };
+                true
+            }
+            None => false,
+        }
+    }
+    fn is_menu_item_enabled(&self, path: &[usize]) -> Option<bool> {
+        common::menu_action(self.as_member(), path).map(|action| unsafe { action.is_enabled() })
+    }
+}
@@ -0,0 +1,37 @@
+use crate::common::{self, *};
+
+use qt_core::KeyboardModifier;
+
+/// Keyboard modifiers held down when an input event happened. `control` is the Command key on macOS, as in Qt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub control: bool,
+    pub alt: bool,
+    pub meta: bool,
+}
+
+impl Modifiers {
+    pub(crate) fn from_qt(modifiers: QFlags<KeyboardModifier>) -> Self {
+        Modifiers {
+            shift: modifiers.test_flag(KeyboardModifier::ShiftModifier),
+            control: modifiers.test_flag(KeyboardModifier::ControlModifier),
+            alt: modifiers.test_flag(KeyboardModifier::AltModifier),
+            meta: modifiers.test_flag(KeyboardModifier::MetaModifier),
+        }
+    }
+}
+
+/// Qt backend callbacks available on every member, including windows and trays.
+pub trait MemberEvents {
+    /// Fires after the palette of the member changed, e.g. when the application switched between light and dark.
+    fn on_palette_changed(&mut self, cb: Option<OnMemberEvent>);
+}
+
+impl<T: controls::Member + ?Sized> MemberEvents for T {
+    fn on_palette_changed(&mut self, cb: Option<OnMemberEvent>) {
+        if let Some(events) = common::member_event_callbacks_mut(self.as_member_mut()) {
+            events.on_palette_changed = cb;
+        }
+    }
+}
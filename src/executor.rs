@@ -0,0 +1,152 @@
+use crate::application::{self, Application, QtApplication, QtSender};
+use crate::common::{self, *};
+
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+
+type LocalTask = Pin<Box<dyn Future<Output = ()>>>;
+
+/// Runs `!Send` futures on the UI thread. Woken tasks are polled from the Qt event loop right after the queued commands.
+#[derive(Default)]
+pub struct Executor {
+    next: usize,
+    tasks: HashMap<usize, LocalTask>,
+    ready: Arc<Mutex<VecDeque<usize>>>,
+}
+
+struct TaskWaker {
+    id: usize,
+    ready: Arc<Mutex<VecDeque<usize>>>,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.ready.lock().unwrap().push_back(self.id);
+        QtApplication::wake();
+    }
+}
+
+impl Executor {
+    pub(crate) fn spawn(&mut self, future: LocalTask) {
+        let id = self.next;
+        self.next += 1;
+        self.tasks.insert(id, future);
+        self.ready.lock().unwrap().push_back(id);
+        QtApplication::wake();
+    }
+}
+
+/// Spawns a future on the UI thread of the running application. Panics if called outside of the UI thread or without an application.
+pub fn spawn_local<F: Future<Output = ()> + 'static>(future: F) {
+    application::application().expect("No application on this thread").inner_mut().executor_mut().spawn(Box::pin(future));
+}
+
+/// Polls every woken task once. Tasks are taken out of the executor while polled, so they may spawn new ones.
+/// A task that panics is dropped.
+pub(crate) fn poll_ready(app: *mut Application) {
+    let ready = unsafe { &mut *app }.inner_mut().executor_mut().ready.clone();
+    let ids = ready.lock().unwrap().drain(..).collect::<Vec<_>>();
+    for id in ids {
+        let task = unsafe { &mut *app }.inner_mut().executor_mut().tasks.remove(&id);
+        if let Some(mut task) = task {
+            let waker = Waker::from(Arc::new(TaskWaker { id: id, ready: ready.clone() }));
+            let mut cx = Context::from_waker(&waker);
+            if let Poll::Pending = common::catch_panic(Poll::Ready(()), || task.as_mut().poll(&mut cx)) {
+                unsafe { &mut *app }.inner_mut().executor_mut().tasks.insert(id, task);
+            }
+        }
+    }
+}
+
+/// `Send`-able handle for other threads and runtimes to hand work and results over to the UI thread.
+#[derive(Clone)]
+pub struct UiHandle {
+    sender: QtSender,
+}
+
+impl UiHandle {
+    pub(crate) fn new(sender: QtSender) -> Self {
+        UiHandle { sender: sender }
+    }
+    /// Runs `f` on the UI thread and resolves to its result. Resolves to `None` if the application is gone before `f` runs.
+    pub fn run<R, F>(&self, f: F) -> UiResult<R>
+    where
+        R: Send + 'static,
+        F: FnOnce(&mut dyn controls::Application) -> R + Send + 'static,
+    {
+        let state = Arc::new(Mutex::new(UiResultState { result: None, done: false, waker: None }));
+        let mut job = Some((f, Completer(state.clone())));
+        // if the command never runs, dropping it drops the completer, which resolves the result to `None`
+        let _ = self.sender.send((move |a: &mut dyn controls::Application| {
+            if let Some((f, completer)) = job.take() {
+                completer.complete(f(a));
+            }
+            false
+        }).into());
+        UiResult { state: state }
+    }
+    /// Runs `f` with the member found by tag, resolves to `None` if there is no such member.
+    pub fn with_member_by_tag<R, F>(&self, tag: String, f: F) -> UiResult<Option<R>>
+    where
+        R: Send + 'static,
+        F: FnOnce(&mut dyn controls::Member) -> R + Send + 'static,
+    {
+        self.run(move |a| a.find_member_mut(types::FindBy::Tag(tag.as_str())).map(f))
+    }
+    /// Runs `f` with the member found by id, resolves to `None` if there is no such member.
+    pub fn with_member_by_id<R, F>(&self, id: ids::Id, f: F) -> UiResult<Option<R>>
+    where
+        R: Send + 'static,
+        F: FnOnce(&mut dyn controls::Member) -> R + Send + 'static,
+    {
+        self.run(move |a| a.find_member_mut(types::FindBy::Id(id)).map(f))
+    }
+}
+
+struct UiResultState<R> {
+    result: Option<R>,
+    done: bool,
+    waker: Option<Waker>,
+}
+
+struct Completer<R>(Arc<Mutex<UiResultState<R>>>);
+
+impl<R> Completer<R> {
+    fn complete(self, result: R) {
+        self.0.lock().unwrap().result = Some(result);
+    }
+}
+impl<R> Drop for Completer<R> {
+    fn drop(&mut self) {
+        let mut state = self.0.lock().unwrap();
+        state.done = true;
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Result of work sent to the UI thread with `UiHandle`. Can be awaited on any runtime.
+pub struct UiResult<R> {
+    state: Arc<Mutex<UiResultState<R>>>,
+}
+
+impl<R> Future for UiResult<R> {
+    type Output = Option<R>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let mut state = self.state.lock().unwrap();
+        if state.done {
+            Poll::Ready(state.result.take())
+        } else {
+            state.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
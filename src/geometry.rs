@@ -0,0 +1,91 @@
+use crate::common::{self, *};
+use crate::settings::Settings;
+
+use qt_gui::QGuiApplication;
+
+const SETTINGS_GROUP: &str = "window_geometry";
+
+/// Saving and restoring position, size, maximized or fullscreen state and screen of windows.
+/// The data is Qt's own geometry format, so it survives changes of the screen setup: a window saved on a screen that is gone
+/// is restored onto the primary one.
+pub trait WindowGeometry {
+    fn save_geometry(&self) -> Vec<u8>;
+    /// Same as `save_geometry`, encoded as base64 to keep in text files.
+    fn save_geometry_string(&self) -> String;
+    /// Returns `false` if the data is not valid geometry.
+    fn restore_geometry(&mut self, data: &[u8]) -> bool;
+    fn restore_geometry_string(&mut self, data: &str) -> bool;
+    /// Keeps the geometry of the window in the application settings under `window_geometry/<tag>`, no matter which group the application
+    /// has entered with `Settings::begin_group`: restores it right away and saves it
+    /// whenever the window closes. Returns `false` if the window has no tag. Restoring on startup takes setting the tag and calling this
+    /// before the window is shown.
+    fn remember_geometry(&mut self, remember: bool) -> bool;
+}
+
+impl<T: controls::Window + ?Sized> WindowGeometry for T {
+    fn save_geometry(&self) -> Vec<u8> {
+        save(common::cast_member_to_qwidget(self.as_member()))
+    }
+    fn save_geometry_string(&self) -> String {
+        let data = self.save_geometry();
+        unsafe { QByteArray::from_slice(&data).to_base64_0a().to_std_string() }
+    }
+    fn restore_geometry(&mut self, data: &[u8]) -> bool {
+        restore(common::cast_member_to_qwidget_mut(self.as_member_mut()), data)
+    }
+    fn restore_geometry_string(&mut self, data: &str) -> bool {
+        let data = unsafe {
+            let bytes = QByteArray::from_base64_1a(&QByteArray::from_slice(data.as_bytes()));
+            std::slice::from_raw_parts(bytes.const_data() as *const u8, bytes.count() as usize).to_vec()
+        };
+        self.restore_geometry(&data)
+    }
+    fn remember_geometry(&mut self, remember: bool) -> bool {
+        let key = match self.as_member().tag() {
+            Some(tag) => format!("{}/{}", SETTINGS_GROUP, tag),
+            None => return false,
+        };
+        if remember {
+            if let Some(data) = Settings::new().bytes(&key) {
+                self.restore_geometry(&data);
+            }
+        }
+        if let Some(events) = common::member_event_callbacks_mut(self.as_member_mut()) {
+            events.window.geometry_key = if remember { Some(key) } else { None };
+        }
+        true
+    }
+}
+
+/// Saves the geometry of a closing window that remembers it.
+pub(crate) fn save_remembered(widget: &QWidget, key: &str) {
+    let data = save(widget);
+    // an own `QSettings` on the same file, so a group the application left open does not move the key
+    let mut settings = Settings::new();
+    settings.set_bytes(key, &data);
+    settings.sync();
+}
+
+fn save(widget: &QWidget) -> Vec<u8> {
+    unsafe {
+        let data = widget.save_geometry();
+        std::slice::from_raw_parts(data.const_data() as *const u8, data.count() as usize).to_vec()
+    }
+}
+fn restore(widget: &mut QWidget, data: &[u8]) -> bool {
+    unsafe {
+        if !widget.restore_geometry(&QByteArray::from_slice(data)) {
+            return false;
+        }
+        // Qt pulls windows back onto the remaining screens, this covers platforms where it does not
+        if QGuiApplication::screen_at(&widget.frame_geometry().center()).is_null() {
+            let screen = QGuiApplication::primary_screen();
+            if !screen.is_null() {
+                let area = screen.available_geometry();
+                let frame = widget.frame_geometry();
+                widget.move_2a(area.x() + cmp::max(0, (area.width() - frame.width()) / 2), area.y() + cmp::max(0, (area.height() - frame.height()) / 2));
+            }
+        }
+        true
+    }
+}
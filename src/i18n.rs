@@ -0,0 +1,236 @@
+use crate::common::{self, *};
+
+use qt_core::{QCoreApplication, QTranslator};
+use qt_widgets::{QAction, QMainWindow, QSystemTrayIcon};
+
+use std::collections::HashMap;
+use std::{fs, io, path};
+
+const TR_CONTEXT: &str = "plygui";
+
+lazy_static! {
+    static ref PROPERTY_TR_SOURCE: CString = CString::new("plygui_tr_source").unwrap();
+    static ref PROPERTY_TR_SHOWN: CString = CString::new("plygui_tr_shown").unwrap();
+}
+
+/// Message catalogs of the application and the language currently in use.
+/// Lookups go to the Rust-side catalogs first, then to the Qt `.qm` translators installed for the language (context `plygui`).
+#[derive(Default)]
+pub struct Translations {
+    language: Option<String>,
+    qm: Vec<(String, String)>,
+    installed: Vec<QBox<QTranslator>>,
+    catalogs: HashMap<String, HashMap<String, String>>,
+}
+
+impl Translations {
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_ref().map(|l| l.as_str())
+    }
+    /// Registers a Qt `.qm` file for `language`. It is loaded when the language is selected.
+    pub fn add_qm<P: AsRef<path::Path>>(&mut self, language: &str, path: P) {
+        self.qm.push((language.to_owned(), path.as_ref().to_string_lossy().into_owned()));
+    }
+    /// Loads a gettext `.mo` file for `language`. Plural forms are not supported, the singular translation is used for them.
+    pub fn add_mo<P: AsRef<path::Path>>(&mut self, language: &str, path: P) -> io::Result<()> {
+        let messages = parse_mo(&fs::read(path)?)?;
+        self.catalog_mut(language).extend(messages);
+        Ok(())
+    }
+    /// Adds messages for `language` from any other source, e.g. resolved Fluent bundles.
+    pub fn add_messages<I: IntoIterator<Item = (String, String)>>(&mut self, language: &str, messages: I) {
+        self.catalog_mut(language).extend(messages);
+    }
+    pub fn tr(&self, source: &str) -> String {
+        if let Some(translated) = self.language.as_ref().and_then(|l| self.catalogs.get(l)).and_then(|c| c.get(source)) {
+            return translated.clone();
+        }
+        let context = CString::new(TR_CONTEXT).unwrap();
+        // Qt cannot look up a text with NUL in it
+        let source_c = match CString::new(source) {
+            Ok(source_c) => source_c,
+            Err(_) => return source.to_owned(),
+        };
+        unsafe { QCoreApplication::translate_2a(context.as_ptr(), source_c.as_ptr()).to_std_string() }
+    }
+
+    pub(crate) fn set_language(&mut self, language: &str) {
+        unsafe {
+            for translator in self.installed.drain(..) {
+                QCoreApplication::remove_translator(translator.as_ptr());
+            }
+            for (_, path) in self.qm.iter().filter(|(l, _)| l == language) {
+                let translator = QTranslator::new_0a();
+                if translator.load_q_string(&QString::from_std_str(path)) {
+                    QCoreApplication::install_translator(translator.as_ptr());
+                    self.installed.push(translator);
+                }
+            }
+        }
+        self.language = Some(language.to_owned());
+    }
+
+    fn catalog_mut(&mut self, language: &str) -> &mut HashMap<String, String> {
+        self.catalogs.entry(language.to_owned()).or_insert_with(HashMap::new)
+    }
+}
+
+/// Re-translates labels of the root and its children, plus window menus and tray menus.
+/// The first label seen on a member is taken as its source text; if the label was changed since the last translation, the new one becomes the source.
+pub(crate) fn retranslate(root: &mut dyn controls::Member, translations: &Translations) {
+    common::for_each_member_mut(root, &mut |member| {
+        let object = unsafe { &*(member.native_id() as *const QObject) };
+        if let Some(labeled) = member.is_has_label_mut() {
+            let current = labeled.label().into_owned();
+            let translated = retranslate_text(object, current, translations);
+            labeled.set_label(translated.into());
+        }
+    });
+    unsafe {
+        let object = Ptr::from_raw(root.native_id() as *const QObject);
+        let window = object.dynamic_cast::<QMainWindow>();
+        if !window.is_null() {
+            retranslate_actions(window.menu_bar().static_upcast::<QWidget>().as_ptr(), translations);
+        }
+        let tray = object.dynamic_cast::<QSystemTrayIcon>();
+        if !tray.is_null() && !tray.context_menu().is_null() {
+            retranslate_actions(tray.context_menu().static_upcast::<QWidget>().as_ptr(), translations);
+        }
+    }
+}
+
+unsafe fn retranslate_actions(widget: Ptr<QWidget>, translations: &Translations) {
+    let actions = widget.actions();
+    for i in 0..actions.size() {
+        let action: Ptr<QAction> = actions.value_1a(i);
+        if action.is_separator() {
+            continue;
+        }
+        // a shortcut shown after a tab is not part of the label
+        let text = action.text().to_std_string();
+        let mut parts = text.splitn(2, '\t');
+        let label = parts.next().unwrap_or("").to_owned();
+        let translated = retranslate_text(&action.static_upcast::<QObject>(), label, translations);
+        action.set_text(&QString::from_std_str(match parts.next() {
+            Some(shortcut) => format!("{}\t{}", translated, shortcut),
+            None => translated,
+        }));
+        let submenu = action.menu();
+        if !submenu.is_null() {
+            retranslate_actions(submenu.static_upcast::<QWidget>().as_ptr(), translations);
+        }
+    }
+}
+fn retranslate_text(object: &QObject, current: String, translations: &Translations) -> String {
+    unsafe {
+        let source = object.property(PROPERTY_TR_SOURCE.as_ptr() as *const i8);
+        let shown = object.property(PROPERTY_TR_SHOWN.as_ptr() as *const i8);
+        let source = if source.is_valid() && shown.is_valid() && shown.to_string().to_std_string() == current {
+            source.to_string().to_std_string()
+        } else {
+            current
+        };
+        let translated = translations.tr(&source);
+        object.set_property(PROPERTY_TR_SOURCE.as_ptr() as *const i8, &QVariant::from_q_string(&QString::from_std_str(&source)));
+        object.set_property(PROPERTY_TR_SHOWN.as_ptr() as *const i8, &QVariant::from_q_string(&QString::from_std_str(&translated)));
+        translated
+    }
+}
+
+fn parse_mo(data: &[u8]) -> io::Result<HashMap<String, String>> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "Invalid .mo file");
+    let read_u32 = |at: usize, big_endian: bool| -> io::Result<usize> {
+        let bytes = data.get(at..at + 4).ok_or_else(invalid)?;
+        let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        Ok(if big_endian { u32::from_be_bytes(bytes) } else { u32::from_le_bytes(bytes) } as usize)
+    };
+    let big_endian = match read_u32(0, false)? {
+        0x950412de => false,
+        0xde120495 => true,
+        _ => return Err(invalid()),
+    };
+    let count = read_u32(8, big_endian)?;
+    let originals = read_u32(12, big_endian)?;
+    let translations = read_u32(16, big_endian)?;
+    let read_string = |table: usize, i: usize| -> io::Result<String> {
+        let len = read_u32(table + i * 8, big_endian)?;
+        let offset = read_u32(table + i * 8 + 4, big_endian)?;
+        let bytes = offset.checked_add(len).and_then(|end| data.get(offset..end)).ok_or_else(invalid)?;
+        // plural forms are separated by NUL, only the singular one is used
+        let bytes = bytes.split(|b| *b == 0).next().unwrap_or(bytes);
+        Ok(String::from_utf8_lossy(bytes).into_owned())
+    };
+    // the tables must fit in the file before the count is trusted
+    let fits = |table: usize| count.checked_mul(8).and_then(|size| size.checked_add(table)).map_or(false, |end| end <= data.len());
+    if !fits(originals) || !fits(translations) {
+        return Err(invalid());
+    }
+    let mut messages = HashMap::with_capacity(count);
+    for i in 0..count {
+        let original = read_string(originals, i)?;
+        // the empty message id holds the catalog metadata
+        if original.is_empty() {
+            continue;
+        }
+        messages.insert(original, read_string(translations, i)?);
+    }
+    Ok(messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_mo;
+
+    fn mo(messages: &[(&str, &str)], big_endian: bool) -> Vec<u8> {
+        let u32_bytes = |value: usize| if big_endian { (value as u32).to_be_bytes() } else { (value as u32).to_le_bytes() };
+        let originals = 28;
+        let translations = originals + messages.len() * 8;
+        let mut strings = translations + messages.len() * 8;
+        let mut data = Vec::new();
+        for value in [0x950412de, 0, messages.len(), originals, translations, 0, 0].iter() {
+            data.extend_from_slice(&u32_bytes(*value));
+        }
+        let mut tail = Vec::new();
+        for text in messages.iter().map(|(original, _)| original).chain(messages.iter().map(|(_, translated)| translated)) {
+            data.extend_from_slice(&u32_bytes(text.len()));
+            data.extend_from_slice(&u32_bytes(strings));
+            tail.extend_from_slice(text.as_bytes());
+            tail.push(0);
+            strings += text.len() + 1;
+        }
+        data.extend(tail);
+        data
+    }
+
+    #[test]
+    fn parse_little_endian() {
+        let messages = parse_mo(&mo(&[("Open", "Öffnen"), ("Save", "Speichern")], false)).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages["Open"], "Öffnen");
+        assert_eq!(messages["Save"], "Speichern");
+    }
+    #[test]
+    fn parse_big_endian() {
+        let messages = parse_mo(&mo(&[("Open", "Ouvrir")], true)).unwrap();
+        assert_eq!(messages["Open"], "Ouvrir");
+    }
+    #[test]
+    fn skip_metadata_and_plurals() {
+        let messages = parse_mo(&mo(&[("", "Content-Type: text/plain; charset=UTF-8\n"), ("file\0files", "Datei\0Dateien")], false)).unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages["file"], "Datei");
+    }
+    #[test]
+    fn reject_truncated() {
+        let data = mo(&[("Open", "Öffnen")], false);
+        assert!(parse_mo(&data[..data.len() - 4]).is_err());
+        assert!(parse_mo(&data[..24]).is_err());
+        assert!(parse_mo(&[]).is_err());
+    }
+    #[test]
+    fn reject_oversized_count() {
+        let mut data = mo(&[("Open", "Öffnen")], false);
+        data[8..12].copy_from_slice(&u32::max_value().to_le_bytes());
+        assert!(parse_mo(&data).is_err());
+    }
+}
@@ -0,0 +1,139 @@
+use crate::common::{self, *};
+use crate::events::Modifiers;
+
+use qt_core::{FocusPolicy, Key};
+use qt_gui::QKeyEvent;
+
+/// Key callback of a control. Returning `true` consumes the event, so the control does not handle it itself.
+pub type OnKey = Box<dyn FnMut(&mut dyn controls::Member, &KeyEvent) -> bool>;
+/// Called with `true` when the control gets the keyboard focus and `false` when it loses it.
+pub type OnFocusChanged = Box<dyn FnMut(&mut dyn controls::Member, bool)>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyEvent {
+    /// Qt key code, e.g. `Key::KeyReturn`.
+    pub key: Key,
+    /// Text the key produces, empty for keys like arrows or function keys.
+    pub text: String,
+    pub modifiers: Modifiers,
+    /// Whether the event comes from holding the key down.
+    pub auto_repeat: bool,
+}
+
+/// Keyboard callbacks of a control, kept in its `EventCallbacks`.
+#[derive(Default)]
+pub struct KeyboardCallbacks {
+    on_key_down: Option<OnKey>,
+    on_key_up: Option<OnKey>,
+    on_focus_changed: Option<OnFocusChanged>,
+}
+
+/// Keyboard input and focus of every control.
+pub trait KeyboardEvents {
+    /// Controls that do not take focus by default, like `Text` or `Image`, start accepting it once a key callback is set.
+    fn on_key_down(&mut self, cb: Option<OnKey>);
+    fn on_key_up(&mut self, cb: Option<OnKey>);
+    fn on_focus_changed(&mut self, cb: Option<OnFocusChanged>);
+    fn focus(&mut self);
+    fn has_focus(&self) -> bool;
+}
+
+impl<T: controls::Control + ?Sized> KeyboardEvents for T {
+    fn on_key_down(&mut self, cb: Option<OnKey>) {
+        accept_focus(self.as_member_mut(), cb.is_some());
+        set(self.as_member_mut(), |keyboard| keyboard.on_key_down = cb);
+    }
+    fn on_key_up(&mut self, cb: Option<OnKey>) {
+        accept_focus(self.as_member_mut(), cb.is_some());
+        set(self.as_member_mut(), |keyboard| keyboard.on_key_up = cb);
+    }
+    fn on_focus_changed(&mut self, cb: Option<OnFocusChanged>) {
+        set(self.as_member_mut(), |keyboard| keyboard.on_focus_changed = cb);
+    }
+    fn focus(&mut self) {
+        unsafe { common::cast_member_to_qwidget_mut(self.as_member_mut()).set_focus_0a() };
+    }
+    fn has_focus(&self) -> bool {
+        unsafe { common::cast_member_to_qwidget(self.as_member()).has_focus() }
+    }
+}
+
+/// Explicit keyboard navigation order inside containers.
+pub trait TabOrder {
+    /// Makes Tab move the focus through the children with the given ids in this order, then on to what followed the last one.
+    /// Returns `false` and changes nothing if one of the ids is not found in the container.
+    fn set_tab_order(&mut self, order: &[ids::Id]) -> bool;
+}
+
+impl<T: controls::Container + ?Sized> TabOrder for T {
+    fn set_tab_order(&mut self, order: &[ids::Id]) -> bool {
+        let mut widgets = Vec::with_capacity(order.len());
+        for id in order {
+            match self.find_control(types::FindBy::Id(*id)) {
+                Some(control) => widgets.push(common::cast_control_to_qwidget(control) as *const QWidget),
+                None => return false,
+            }
+        }
+        for pair in widgets.windows(2) {
+            unsafe { QWidget::set_tab_order(Ptr::from_raw(pair[0]), Ptr::from_raw(pair[1])) };
+        }
+        true
+    }
+}
+
+fn set<F: FnOnce(&mut KeyboardCallbacks)>(member: &mut dyn controls::Member, f: F) {
+    if let Some(events) = common::member_event_callbacks_mut(member) {
+        f(&mut events.keyboard);
+    }
+}
+fn accept_focus(member: &mut dyn controls::Member, accept: bool) {
+    let widget = common::cast_member_to_qwidget_mut(member);
+    unsafe {
+        if accept && widget.focus_policy() == FocusPolicy::NoFocus {
+            widget.set_focus_policy(FocusPolicy::StrongFocus);
+        }
+    }
+}
+
+/// Runs the keyboard or focus callback of `object` for the event. Returns `true` if a key callback consumed it.
+pub(crate) fn dispatch(object: &mut QObject, event: &mut QEvent) -> bool {
+    let events = match common::event_callbacks_mut(object) {
+        Some(events) => events,
+        None => return false,
+    };
+    let member = match common::cast_qobject_to_base_mut(object) {
+        Some(base) => base.as_member_mut(),
+        None => return false,
+    };
+    match unsafe { event.type_() } {
+        QEventType::KeyPress | QEventType::KeyRelease => {
+            let cb = if unsafe { event.type_() } == QEventType::KeyPress {
+                events.keyboard.on_key_down.as_mut()
+            } else {
+                events.keyboard.on_key_up.as_mut()
+            };
+            match cb {
+                Some(cb) => {
+                    let key_event = unsafe {
+                        let key = Ptr::from_raw(event as *const QEvent).static_downcast::<QKeyEvent>();
+                        KeyEvent {
+                            key: Key::from(key.key()),
+                            text: key.text().to_std_string(),
+                            modifiers: Modifiers::from_qt(key.modifiers()),
+                            auto_repeat: key.is_auto_repeat(),
+                        }
+                    };
+                    (cb.as_mut())(member, &key_event)
+                }
+                None => false,
+            }
+        }
+        QEventType::FocusIn | QEventType::FocusOut => {
+            if let Some(ref mut cb) = events.keyboard.on_focus_changed {
+                (cb.as_mut())(member, unsafe { event.type_() } == QEventType::FocusIn);
+            }
+            false
+        }
+        _ => false,
+    }
+}
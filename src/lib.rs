@@ -8,12 +8,35 @@ extern crate lazy_static;
 pub use qt_core;
 pub use qt_core_custom_events;
 pub use qt_gui;
+pub use qt_network;
 pub use qt_widgets;
 
 #[macro_use]
 pub mod common;
 
 pub mod common_api;
+pub mod automation;
+pub mod capture;
+pub mod clipboard;
+pub mod command_line;
+pub mod context_menu;
+pub mod enabled;
+pub mod events;
+pub mod executor;
+pub mod geometry;
+pub mod i18n;
+pub mod keyboard;
+pub mod logging;
+pub mod mouse;
+pub mod palette;
+pub mod screen;
+pub mod settings;
+pub mod shortcut;
+pub mod snapshot;
+pub mod style;
+pub mod timer;
+pub mod tooltip;
+pub mod window_state;
 
 mod application;
 mod button;
@@ -32,6 +55,8 @@ mod tree;
 default_markup_register_members!();
 default_pub_use!();
 
+pub use crate::application::{OnAnotherInstance, OnPanic, PanicAction, QtApplication, QtSender};
+
 pub use plygui_api::controls::*;
 pub use plygui_api::ids::*;
 pub use plygui_api::types::{*, self};
@@ -13,6 +13,9 @@ pub struct QtList {
 }
 impl ItemClickableInner for QtList {
     fn item_click(&mut self, i: &[usize], item_view: &mut dyn controls::Control, _skip_callbacks: bool) {
+        if !unsafe { self.base.widget.is_enabled() } {
+            return;
+        }
         let this = common::cast_qobject_to_uimember_mut::<List>(&mut self.base.as_qwidget()).unwrap();
         if let Some(ref mut callback) = self.h_left_clicked.0 {
             (callback.as_mut())(this, i, item_view)
@@ -63,7 +66,7 @@ impl<O: controls::List> NewListInner<O> for QtList {
         unsafe {
             let ptr = ptr as *const _ as u64;
             let obj = ll.base.widget.static_upcast::<QObject>().as_mut_raw_ptr();
-            ll.h_left_clicked.1 = SlotNoArgs::new(NullPtr, move || {
+            ll.h_left_clicked.1 = SlotNoArgs::new(NullPtr, move || common::catch_panic((), || {
                 let this = cast_qobject_to_uimember_mut::<List>(&mut *obj).unwrap();
                 let clicked = this.inner().inner().inner().inner().inner().base.widget.current_item();
                 let i = this.inner().inner().inner().inner().inner().base.widget.row(clicked);
@@ -73,7 +76,7 @@ impl<O: controls::List> NewListInner<O> for QtList {
                     let this = cast_qobject_to_uimember_mut::<O>(&mut *obj).unwrap();
                     (cb.as_mut())(this, &[i as usize], clicked.as_mut());
                 }
-            });
+            }));
             ll.base.widget.item_clicked().connect(&ll.h_left_clicked.1);
             let qo = ll.base.widget.static_upcast::<QObject>();
             qo.set_property(PROPERTY.as_ptr() as *const i8, &QVariant::from_u64(ptr));
@@ -0,0 +1,59 @@
+use qt_core::{q_install_message_handler, QMessageLogContext, QString, QtMsgType};
+
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static PANIC_ON_CRITICAL: AtomicBool = AtomicBool::new(false);
+
+/// Routes Qt debug, info, warning, critical and fatal messages to the `log` facade under the `qt::<category>` target.
+/// Until a logger is set, messages go to stderr as Qt would print them.
+pub(crate) fn install() {
+    unsafe { q_install_message_handler(Some(message_handler)) };
+}
+
+/// Makes Qt critical messages panic in debug builds, so that a backtrace points at the call that caused them.
+/// The panic cannot unwind through Qt and aborts the process. Has no effect in release builds.
+pub fn set_panic_on_critical(panic: bool) {
+    PANIC_ON_CRITICAL.store(panic, Ordering::Relaxed);
+}
+
+extern "C" fn message_handler(msg_type: QtMsgType, context: *const QMessageLogContext, message: *const QString) {
+    let message = unsafe { (*message).to_std_string() };
+    let (category, file, line) = unsafe {
+        let context = &*context;
+        (c_str(context.category()), c_str(context.file()), context.line())
+    };
+    let level = match msg_type {
+        QtMsgType::QtDebugMsg => log::Level::Debug,
+        QtMsgType::QtInfoMsg => log::Level::Info,
+        QtMsgType::QtWarningMsg => log::Level::Warn,
+        _ => log::Level::Error,
+    };
+    let target = format!("qt::{}", category.unwrap_or("default"));
+    if log::max_level() == log::LevelFilter::Off {
+        eprintln!("{}", message);
+    } else if level <= log::max_level() && log::logger().enabled(&log::Metadata::builder().level(level).target(&target).build()) {
+        log::logger().log(
+            &log::Record::builder()
+                .level(level)
+                .target(&target)
+                .file(file)
+                .line(if line > 0 { Some(line as u32) } else { None })
+                .args(format_args!("{}", message))
+                .build(),
+        );
+    }
+    if cfg!(debug_assertions) && msg_type == QtMsgType::QtCriticalMsg && PANIC_ON_CRITICAL.load(Ordering::Relaxed) {
+        panic!("Qt critical: {}", message);
+    }
+}
+
+// Qt leaves context fields null in release builds of Qt itself
+unsafe fn c_str<'a>(ptr: *const c_char) -> Option<&'a str> {
+    if ptr.is_null() {
+        None
+    } else {
+        CStr::from_ptr(ptr).to_str().ok()
+    }
+}
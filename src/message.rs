@@ -34,7 +34,7 @@ impl MessageInner for QtMessage {
             AMessage::with_inner(
                 QtMessage {
                     message: unsafe { QMessageBox::new() },
-                    filter: CustomEventFilter::new(event_handler),
+                    filter: CustomEventFilter::new(|object, event| common::catch_panic(false, || event_handler(object, event))),
                     actions: actions,
                 }
             ),
@@ -0,0 +1,208 @@
+use crate::common::{self, *};
+use crate::events::Modifiers;
+
+use qt_core::{MouseButton as QMouseButton, QPoint};
+use qt_gui::{QCursor, QEnterEvent, QMouseEvent, QWheelEvent};
+use qt_widgets::QAbstractScrollArea;
+
+/// Mouse callback of a control. Returning `true` consumes the event, so the control does not handle it itself.
+pub type OnMouse = Box<dyn FnMut(&mut dyn controls::Member, &MouseEvent) -> bool>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+    Back,
+    Forward,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseEventKind {
+    Press,
+    Release,
+    DoubleClick,
+    Move,
+    Enter,
+    Leave,
+    Wheel,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MouseEvent {
+    pub kind: MouseEventKind,
+    /// Cursor position relative to the control.
+    pub position: (i32, i32),
+    /// The button that was pressed or released, if any.
+    pub button: Option<MouseButton>,
+    /// Buttons held down when the event happened.
+    pub buttons: Vec<MouseButton>,
+    pub modifiers: Modifiers,
+    /// Scroll of a wheel event as `(horizontal, vertical)` in eighths of a degree, most mice step by 120. Zero for other events.
+    pub delta: (i32, i32),
+}
+
+/// Mouse callbacks of a control, kept in its `EventCallbacks`.
+#[derive(Default)]
+pub struct MouseCallbacks {
+    on_press: Option<OnMouse>,
+    on_release: Option<OnMouse>,
+    on_double_click: Option<OnMouse>,
+    on_move: Option<OnMouse>,
+    on_enter: Option<OnMouse>,
+    on_leave: Option<OnMouse>,
+    on_wheel: Option<OnMouse>,
+    // item views get their mouse events on the viewport, which the control filter does not see.
+    // Only this filter dispatches them, events the viewport ignores propagate to the view itself
+    viewport_filter: Option<QBox<CustomEventFilter>>,
+}
+
+/// Mouse callbacks available on every control.
+pub trait MouseEvents {
+    fn on_mouse_press(&mut self, cb: Option<OnMouse>);
+    fn on_mouse_release(&mut self, cb: Option<OnMouse>);
+    fn on_mouse_double_click(&mut self, cb: Option<OnMouse>);
+    /// Fires on every cursor move over the control, with or without a button held down.
+    fn on_mouse_move(&mut self, cb: Option<OnMouse>);
+    fn on_mouse_enter(&mut self, cb: Option<OnMouse>);
+    fn on_mouse_leave(&mut self, cb: Option<OnMouse>);
+    fn on_wheel(&mut self, cb: Option<OnMouse>);
+}
+
+impl<T: controls::Control + ?Sized> MouseEvents for T {
+    fn on_mouse_press(&mut self, cb: Option<OnMouse>) {
+        set(self.as_member_mut(), |mouse| mouse.on_press = cb);
+    }
+    fn on_mouse_release(&mut self, cb: Option<OnMouse>) {
+        set(self.as_member_mut(), |mouse| mouse.on_release = cb);
+    }
+    fn on_mouse_double_click(&mut self, cb: Option<OnMouse>) {
+        set(self.as_member_mut(), |mouse| mouse.on_double_click = cb);
+    }
+    fn on_mouse_move(&mut self, cb: Option<OnMouse>) {
+        let tracking = cb.is_some();
+        set(self.as_member_mut(), |mouse| mouse.on_move = cb);
+        // without tracking Qt reports moves only while a button is held down
+        unsafe {
+            let object = Ptr::from_raw(self.native_id() as *const QObject);
+            object.dynamic_cast::<QWidget>().set_mouse_tracking(tracking);
+            let area = object.dynamic_cast::<QAbstractScrollArea>();
+            if !area.is_null() {
+                area.viewport().set_mouse_tracking(tracking);
+            }
+        }
+    }
+    fn on_mouse_enter(&mut self, cb: Option<OnMouse>) {
+        set(self.as_member_mut(), |mouse| mouse.on_enter = cb);
+    }
+    fn on_mouse_leave(&mut self, cb: Option<OnMouse>) {
+        set(self.as_member_mut(), |mouse| mouse.on_leave = cb);
+    }
+    fn on_wheel(&mut self, cb: Option<OnMouse>) {
+        set(self.as_member_mut(), |mouse| mouse.on_wheel = cb);
+    }
+}
+
+fn set<F: FnOnce(&mut MouseCallbacks)>(member: &mut dyn controls::Member, f: F) {
+    let object = member.native_id() as *mut QObject;
+    if let Some(events) = common::member_event_callbacks_mut(member) {
+        f(&mut events.mouse);
+        if events.mouse.viewport_filter.is_none() {
+            unsafe {
+                let area = Ptr::from_raw(object as *const QObject).dynamic_cast::<QAbstractScrollArea>();
+                if !area.is_null() {
+                    let filter = CustomEventFilter::new(move |_, event| common::catch_panic(false, || fire(&mut *object, event)));
+                    area.viewport().install_event_filter(filter.static_upcast::<QObject>().as_ptr());
+                    events.mouse.viewport_filter = Some(filter);
+                }
+            }
+        }
+    }
+}
+
+/// Runs the mouse callback of `object` for the event. Returns `true` if the callback consumed it.
+/// Scroll areas are skipped, their callbacks run from the viewport filter.
+pub(crate) fn dispatch(object: &mut QObject, event: &mut QEvent) -> bool {
+    if unsafe { !Ptr::from_raw(object as *const QObject).dynamic_cast::<QAbstractScrollArea>().is_null() } {
+        return false;
+    }
+    fire(object, event)
+}
+fn fire(object: &mut QObject, event: &mut QEvent) -> bool {
+    let kind = match unsafe { event.type_() } {
+        QEventType::MouseButtonPress => MouseEventKind::Press,
+        QEventType::MouseButtonRelease => MouseEventKind::Release,
+        QEventType::MouseButtonDblClick => MouseEventKind::DoubleClick,
+        QEventType::MouseMove => MouseEventKind::Move,
+        QEventType::Enter => MouseEventKind::Enter,
+        QEventType::Leave => MouseEventKind::Leave,
+        QEventType::Wheel => MouseEventKind::Wheel,
+        _ => return false,
+    };
+    let events = match common::event_callbacks_mut(object) {
+        Some(events) => events,
+        None => return false,
+    };
+    let cb = match kind {
+        MouseEventKind::Press => events.mouse.on_press.as_mut(),
+        MouseEventKind::Release => events.mouse.on_release.as_mut(),
+        MouseEventKind::DoubleClick => events.mouse.on_double_click.as_mut(),
+        MouseEventKind::Move => events.mouse.on_move.as_mut(),
+        MouseEventKind::Enter => events.mouse.on_enter.as_mut(),
+        MouseEventKind::Leave => events.mouse.on_leave.as_mut(),
+        MouseEventKind::Wheel => events.mouse.on_wheel.as_mut(),
+    };
+    let cb = match cb {
+        Some(cb) => cb,
+        None => return false,
+    };
+    let member = match common::cast_qobject_to_base_mut(object) {
+        Some(base) => base.as_member_mut(),
+        None => return false,
+    };
+    let mouse_event = unsafe { mouse_event(object, event, kind) };
+    (cb.as_mut())(member, &mouse_event)
+}
+
+unsafe fn mouse_event(object: &QObject, event: &QEvent, kind: MouseEventKind) -> MouseEvent {
+    let event = Ptr::from_raw(event as *const QEvent);
+    let (global, button, buttons, modifiers, delta) = match kind {
+        MouseEventKind::Wheel => {
+            let wheel = event.static_downcast::<QWheelEvent>();
+            let delta = wheel.angle_delta();
+            (wheel.global_position().to_point(), None, wheel.buttons(), wheel.modifiers(), (delta.x(), delta.y()))
+        }
+        MouseEventKind::Enter => {
+            let enter = event.static_downcast::<QEnterEvent>();
+            (QPoint::new_2a(enter.global_x(), enter.global_y()), None, qt_gui::QGuiApplication::mouse_buttons(), qt_gui::QGuiApplication::keyboard_modifiers(), (0, 0))
+        }
+        MouseEventKind::Leave => (QCursor::pos_0a(), None, qt_gui::QGuiApplication::mouse_buttons(), qt_gui::QGuiApplication::keyboard_modifiers(), (0, 0)),
+        _ => {
+            let mouse = event.static_downcast::<QMouseEvent>();
+            (QPoint::new_2a(mouse.global_x(), mouse.global_y()), qbutton_to_button(mouse.button()), mouse.buttons(), mouse.modifiers(), (0, 0))
+        }
+    };
+    let position = Ptr::from_raw(object as *const QObject).dynamic_cast::<QWidget>().map_from_global(&global);
+    MouseEvent {
+        kind: kind,
+        position: (position.x(), position.y()),
+        button: button,
+        buttons: [QMouseButton::LeftButton, QMouseButton::RightButton, QMouseButton::MiddleButton, QMouseButton::BackButton, QMouseButton::ForwardButton]
+            .iter()
+            .filter(|b| buttons.test_flag(**b))
+            .filter_map(|b| qbutton_to_button(*b))
+            .collect(),
+        modifiers: Modifiers::from_qt(modifiers),
+        delta: delta,
+    }
+}
+fn qbutton_to_button(button: QMouseButton) -> Option<MouseButton> {
+    match button {
+        QMouseButton::LeftButton => Some(MouseButton::Left),
+        QMouseButton::RightButton => Some(MouseButton::Right),
+        QMouseButton::MiddleButton => Some(MouseButton::Middle),
+        QMouseButton::BackButton => Some(MouseButton::Back),
+        QMouseButton::ForwardButton => Some(MouseButton::Forward),
+        _ => None,
+    }
+}
@@ -0,0 +1,135 @@
+use crate::common::{self, *};
+
+use qt_gui::q_palette::{ColorGroup, ColorRole as QColorRole};
+use qt_gui::{QColor, QPalette};
+use qt_widgets::QApplication;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorRole {
+    Window,
+    WindowText,
+    Base,
+    AlternateBase,
+    ToolTipBase,
+    ToolTipText,
+    Text,
+    Button,
+    ButtonText,
+    BrightText,
+    Highlight,
+    HighlightedText,
+    Link,
+    LinkVisited,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Palette {
+    /// The palette of the platform, as it was before the first `set_palette`.
+    System,
+    Light,
+    Dark,
+    /// The system palette with the given roles overridden.
+    Custom(Vec<(ColorRole, image::Rgba<u8>)>),
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette::System
+    }
+}
+
+/// Applies `palette` on top of the platform palette `system`.
+pub(crate) fn apply(palette: &Palette, system: &QPalette) {
+    unsafe {
+        let qpalette = QPalette::new_copy(system);
+        match palette {
+            Palette::System => {}
+            Palette::Light => {
+                set_colors(&qpalette, &LIGHT);
+                qpalette.set_color_3a(ColorGroup::Disabled, QColorRole::Text, &QColor::from_rgb_3a(160, 160, 160));
+                qpalette.set_color_3a(ColorGroup::Disabled, QColorRole::ButtonText, &QColor::from_rgb_3a(160, 160, 160));
+                qpalette.set_color_3a(ColorGroup::Disabled, QColorRole::WindowText, &QColor::from_rgb_3a(160, 160, 160));
+            }
+            Palette::Dark => {
+                set_colors(&qpalette, &DARK);
+                qpalette.set_color_3a(ColorGroup::Disabled, QColorRole::Text, &QColor::from_rgb_3a(127, 127, 127));
+                qpalette.set_color_3a(ColorGroup::Disabled, QColorRole::ButtonText, &QColor::from_rgb_3a(127, 127, 127));
+                qpalette.set_color_3a(ColorGroup::Disabled, QColorRole::WindowText, &QColor::from_rgb_3a(127, 127, 127));
+            }
+            Palette::Custom(roles) => {
+                for (role, color) in roles {
+                    qpalette.set_color_2a(role_to_qrole(*role), &QColor::from_rgb_4a(color[0] as i32, color[1] as i32, color[2] as i32, color[3] as i32));
+                }
+            }
+        }
+        QApplication::set_palette_1a(&qpalette);
+    }
+}
+/// Runs `on_palette_changed` of every tray, as they have no widget to receive `QEvent::PaletteChange`.
+pub(crate) fn notify_trays(app: &mut crate::application::Application) {
+    for tray in app.base.trays.iter_mut() {
+        let tray2 = unsafe { &mut *(tray.as_mut() as *mut crate::tray::Tray) };
+        if let Some(events) = common::member_event_callbacks_mut(tray.as_member_mut()) {
+            if let Some(ref mut cb) = events.on_palette_changed {
+                (cb.as_mut())(tray2.as_member_mut());
+            }
+        }
+    }
+}
+
+unsafe fn set_colors(qpalette: &QPalette, colors: &[(ColorRole, (u8, u8, u8))]) {
+    for (role, (r, g, b)) in colors {
+        qpalette.set_color_2a(role_to_qrole(*role), &QColor::from_rgb_3a(*r as i32, *g as i32, *b as i32));
+    }
+}
+fn role_to_qrole(role: ColorRole) -> QColorRole {
+    match role {
+        ColorRole::Window => QColorRole::Window,
+        ColorRole::WindowText => QColorRole::WindowText,
+        ColorRole::Base => QColorRole::Base,
+        ColorRole::AlternateBase => QColorRole::AlternateBase,
+        ColorRole::ToolTipBase => QColorRole::ToolTipBase,
+        ColorRole::ToolTipText => QColorRole::ToolTipText,
+        ColorRole::Text => QColorRole::Text,
+        ColorRole::Button => QColorRole::Button,
+        ColorRole::ButtonText => QColorRole::ButtonText,
+        ColorRole::BrightText => QColorRole::BrightText,
+        ColorRole::Highlight => QColorRole::Highlight,
+        ColorRole::HighlightedText => QColorRole::HighlightedText,
+        ColorRole::Link => QColorRole::Link,
+        ColorRole::LinkVisited => QColorRole::LinkVisited,
+    }
+}
+
+const LIGHT: [(ColorRole, (u8, u8, u8)); 14] = [
+    (ColorRole::Window, (239, 239, 239)),
+    (ColorRole::WindowText, (0, 0, 0)),
+    (ColorRole::Base, (255, 255, 255)),
+    (ColorRole::AlternateBase, (247, 247, 247)),
+    (ColorRole::ToolTipBase, (255, 255, 220)),
+    (ColorRole::ToolTipText, (0, 0, 0)),
+    (ColorRole::Text, (0, 0, 0)),
+    (ColorRole::Button, (239, 239, 239)),
+    (ColorRole::ButtonText, (0, 0, 0)),
+    (ColorRole::BrightText, (255, 255, 255)),
+    (ColorRole::Highlight, (48, 140, 198)),
+    (ColorRole::HighlightedText, (255, 255, 255)),
+    (ColorRole::Link, (0, 0, 255)),
+    (ColorRole::LinkVisited, (255, 0, 255)),
+];
+const DARK: [(ColorRole, (u8, u8, u8)); 14] = [
+    (ColorRole::Window, (53, 53, 53)),
+    (ColorRole::WindowText, (255, 255, 255)),
+    (ColorRole::Base, (35, 35, 35)),
+    (ColorRole::AlternateBase, (53, 53, 53)),
+    (ColorRole::ToolTipBase, (25, 25, 25)),
+    (ColorRole::ToolTipText, (255, 255, 255)),
+    (ColorRole::Text, (255, 255, 255)),
+    (ColorRole::Button, (53, 53, 53)),
+    (ColorRole::ButtonText, (255, 255, 255)),
+    (ColorRole::BrightText, (255, 0, 0)),
+    (ColorRole::Highlight, (42, 130, 218)),
+    (ColorRole::HighlightedText, (35, 35, 35)),
+    (ColorRole::Link, (42, 130, 218)),
+    (ColorRole::LinkVisited, (130, 90, 218)),
+];
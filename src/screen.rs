@@ -0,0 +1,214 @@
+use crate::application::application;
+use crate::common::{self, *};
+
+use qt_core::QRect;
+use qt_gui::{QGuiApplication, QScreen, SlotOfQScreen};
+use qt_widgets::QApplication;
+
+pub type OnScreensChanged = Box<dyn FnMut(&mut dyn controls::Application, ScreenChange)>;
+
+/// Rectangle on the virtual desktop, in device-independent pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScreenRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Screen {
+    /// Platform name of the screen, e.g. the output name on X11. Screens are addressed by it.
+    pub name: String,
+    pub primary: bool,
+    pub geometry: ScreenRect,
+    /// Geometry without taskbars, docks and panels.
+    pub available_geometry: ScreenRect,
+    pub logical_dpi: f64,
+    pub physical_dpi: f64,
+    pub device_pixel_ratio: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScreenChange {
+    Added(Screen),
+    /// The name of the screen that is gone.
+    Removed(String),
+    /// Geometry or DPI of the screen changed.
+    Changed(Screen),
+}
+
+impl Screen {
+    unsafe fn from_qscreen(screen: Ptr<QScreen>) -> Self {
+        let primary = QGuiApplication::primary_screen();
+        Screen {
+            name: screen.name().to_std_string(),
+            primary: !primary.is_null() && primary.as_raw_ptr() == screen.as_raw_ptr(),
+            geometry: qrect_to_rect(&screen.geometry()),
+            available_geometry: qrect_to_rect(&screen.available_geometry()),
+            logical_dpi: screen.logical_dots_per_inch(),
+            physical_dpi: screen.physical_dots_per_inch(),
+            device_pixel_ratio: screen.device_pixel_ratio(),
+        }
+    }
+}
+
+pub fn screens() -> Vec<Screen> {
+    unsafe { qscreens().into_iter().map(|screen| Screen::from_qscreen(screen)).collect() }
+}
+pub fn primary_screen() -> Option<Screen> {
+    unsafe {
+        let screen = QGuiApplication::primary_screen();
+        if screen.is_null() {
+            None
+        } else {
+            Some(Screen::from_qscreen(screen.as_ptr()))
+        }
+    }
+}
+
+/// Creates a window on the named screen, or on the primary one if there is no such screen. The window is shown only once it is in place,
+/// while a window created with `with_params` and then moved with `ScreenPlacement::move_to_screen` appears on the primary screen first.
+/// `WindowStartSize::Fullscreen` covers the named screen.
+pub fn window_on_screen<S: AsRef<str>>(app: &mut dyn controls::Application, screen: &str, title: S, start_size: types::WindowStartSize, menu: types::Menu) -> Box<dyn controls::Window> {
+    let fill = match start_size {
+        types::WindowStartSize::Fullscreen => true,
+        _ => false,
+    };
+    let mut window = crate::window::QtWindow::create(app, title.as_ref(), start_size, menu);
+    window.move_to_screen(screen, fill);
+    unsafe { common::cast_member_to_qwidget(window.as_member()).show() };
+    window
+}
+
+/// Placement of windows on the screens of a multi-monitor setup.
+pub trait ScreenPlacement {
+    /// The screen holding the center of the window.
+    fn screen(&self) -> Option<Screen>;
+    /// Centers the window in the available area of the named screen. With `fill`, the window is also resized to cover the whole screen,
+    /// like `WindowStartSize::Fullscreen` does on the primary one. Returns `false` if there is no such screen.
+    fn move_to_screen(&mut self, name: &str, fill: bool) -> bool;
+}
+
+impl<T: controls::Window + ?Sized> ScreenPlacement for T {
+    fn screen(&self) -> Option<Screen> {
+        let widget = common::cast_member_to_qwidget(self.as_member());
+        unsafe {
+            let screen = QGuiApplication::screen_at(&widget.frame_geometry().center());
+            if screen.is_null() {
+                None
+            } else {
+                Some(Screen::from_qscreen(screen.as_ptr()))
+            }
+        }
+    }
+    fn move_to_screen(&mut self, name: &str, fill: bool) -> bool {
+        let screen = match unsafe { qscreens() }.into_iter().find(|screen| unsafe { screen.name().to_std_string() } == name) {
+            Some(screen) => screen,
+            None => return false,
+        };
+        let widget = common::cast_member_to_qwidget_mut(self.as_member_mut());
+        unsafe {
+            if !widget.window_handle().is_null() {
+                widget.window_handle().set_screen(screen);
+            }
+            if fill {
+                widget.set_geometry_1a(&screen.geometry());
+            } else {
+                let area = screen.available_geometry();
+                let frame = widget.frame_geometry();
+                let x = area.x() + cmp::max(0, (area.width() - frame.width()) / 2);
+                let y = area.y() + cmp::max(0, (area.height() - frame.height()) / 2);
+                widget.move_2a(x, y);
+            }
+        }
+        true
+    }
+}
+
+/// Tracks added and removed screens and the geometry and DPI of each one for `on_screens_changed`.
+pub(crate) struct ScreenWatcher {
+    on_change: Option<OnScreensChanged>,
+    h_added: QBox<SlotOfQScreen>,
+    h_removed: QBox<SlotOfQScreen>,
+    watched: Vec<(Ptr<QScreen>, QBox<SlotNoArgs>)>,
+}
+
+impl ScreenWatcher {
+    pub(crate) fn new(app: &QApplication) -> Self {
+        let mut w = ScreenWatcher {
+            on_change: None,
+            h_added: unsafe { SlotOfQScreen::new(NullPtr, move |screen| common::catch_panic((), || added(screen))) },
+            h_removed: unsafe { SlotOfQScreen::new(NullPtr, move |screen| common::catch_panic((), || removed(screen))) },
+            watched: Vec::new(),
+        };
+        unsafe {
+            app.screen_added().connect(&w.h_added);
+            app.screen_removed().connect(&w.h_removed);
+            for screen in qscreens() {
+                w.watch(screen);
+            }
+        }
+        w
+    }
+    pub(crate) fn on_change(&mut self, cb: Option<OnScreensChanged>) {
+        self.on_change = cb;
+    }
+
+    unsafe fn watch(&mut self, screen: Ptr<QScreen>) {
+        let changed = SlotNoArgs::new(NullPtr, move || common::catch_panic((), || notify(ScreenChange::Changed(Screen::from_qscreen(screen)))));
+        screen.geometry_changed().connect(&changed);
+        screen.available_geometry_changed().connect(&changed);
+        screen.logical_dots_per_inch_changed().connect(&changed);
+        screen.physical_dots_per_inch_changed().connect(&changed);
+        self.watched.push((screen, changed));
+    }
+}
+
+fn added(screen: Ptr<QScreen>) {
+    if let Some(watcher) = application().and_then(|app| app.inner_mut().screen_watcher_mut()) {
+        unsafe { watcher.watch(screen) };
+    }
+    notify(ScreenChange::Added(unsafe { Screen::from_qscreen(screen) }));
+}
+fn removed(screen: Ptr<QScreen>) {
+    if let Some(watcher) = application().and_then(|app| app.inner_mut().screen_watcher_mut()) {
+        watcher.watched.retain(|(watched, _)| unsafe { watched.as_raw_ptr() != screen.as_raw_ptr() });
+    }
+    notify(ScreenChange::Removed(unsafe { screen.name().to_std_string() }));
+}
+fn notify(change: ScreenChange) {
+    let app = match application() {
+        Some(app) => app,
+        None => return,
+    };
+    let app2 = app as *mut crate::application::Application;
+    let watcher = match app.inner_mut().screen_watcher_mut() {
+        Some(watcher) => watcher,
+        None => return,
+    };
+    // taken out while running, so the callback may replace itself
+    if let Some(mut cb) = watcher.on_change.take() {
+        (cb.as_mut())(unsafe { &mut *app2 }, change);
+        if let Some(watcher) = unsafe { &mut *app2 }.inner_mut().screen_watcher_mut() {
+            if watcher.on_change.is_none() {
+                watcher.on_change = Some(cb);
+            }
+        }
+    }
+}
+
+unsafe fn qscreens() -> Vec<Ptr<QScreen>> {
+    let screens = QGuiApplication::screens();
+    (0..screens.size()).map(|i| screens.value_1a(i)).collect()
+}
+fn qrect_to_rect(rect: &QRect) -> ScreenRect {
+    unsafe {
+        ScreenRect {
+            x: rect.x(),
+            y: rect.y(),
+            width: rect.width(),
+            height: rect.height(),
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use crate::common::*;
+
+use qt_core::q_settings::{Format as QSettingsFormat, Scope as QSettingsScope};
+use qt_core::{QCoreApplication, QSettings};
+
+pub type OnSettingChanged = Box<dyn FnMut(&str)>;
+
+/// Persistent key-value store in INI format, kept per user under the application name.
+/// Keys may be nested with `/`, or relative to the group entered with `begin_group`.
+pub struct Settings {
+    settings: QBox<QSettings>,
+    on_change: Option<OnSettingChanged>,
+}
+
+impl Settings {
+    pub(crate) fn new() -> Self {
+        let settings = unsafe {
+            let name = QCoreApplication::application_name();
+            QSettings::from_format_scope_q_string_q_string(QSettingsFormat::IniFormat, QSettingsScope::UserScope, &name, &name)
+        };
+        Settings { settings: settings, on_change: None }
+    }
+    /// Location of the backing INI file.
+    pub fn path(&self) -> String {
+        unsafe { self.settings.file_name().to_std_string() }
+    }
+    pub fn begin_group(&mut self, name: &str) {
+        unsafe { self.settings.begin_group(&QString::from_std_str(name)) };
+    }
+    pub fn end_group(&mut self) {
+        unsafe { self.settings.end_group() };
+    }
+    pub fn keys(&self) -> Vec<String> {
+        unsafe { qstring_list_to_vec(&self.settings.child_keys()) }
+    }
+    pub fn groups(&self) -> Vec<String> {
+        unsafe { qstring_list_to_vec(&self.settings.child_groups()) }
+    }
+    pub fn contains(&self, key: &str) -> bool {
+        unsafe { self.settings.contains(&QString::from_std_str(key)) }
+    }
+    pub fn remove(&mut self, key: &str) {
+        unsafe { self.settings.remove(&QString::from_std_str(key)) };
+        self.changed(key);
+    }
+    /// Writes pending changes to disk. Qt also does it periodically and on exit.
+    pub fn sync(&mut self) {
+        unsafe { self.settings.sync() };
+    }
+    /// Fires for every key set or removed through this `Settings`, with the key including the current group.
+    pub fn on_change(&mut self, cb: Option<OnSettingChanged>) {
+        self.on_change = cb;
+    }
+
+    pub fn string(&self, key: &str) -> Option<String> {
+        self.value(key).map(|v| unsafe { v.to_string().to_std_string() })
+    }
+    pub fn set_string(&mut self, key: &str, value: &str) {
+        self.set_value(key, unsafe { QVariant::from_q_string(&QString::from_std_str(value)) });
+    }
+    pub fn int(&self, key: &str) -> Option<i64> {
+        self.value(key).and_then(|v| unsafe {
+            let mut ok = false;
+            let value = v.to_long_long_1a(&mut ok);
+            if ok { Some(value) } else { None }
+        })
+    }
+    pub fn set_int(&mut self, key: &str, value: i64) {
+        self.set_value(key, unsafe { QVariant::from_i64(value) });
+    }
+    pub fn float(&self, key: &str) -> Option<f64> {
+        self.value(key).and_then(|v| unsafe {
+            let mut ok = false;
+            let value = v.to_double_1a(&mut ok);
+            if ok { Some(value) } else { None }
+        })
+    }
+    pub fn set_float(&mut self, key: &str, value: f64) {
+        self.set_value(key, unsafe { QVariant::from_double(value) });
+    }
+    pub fn bool(&self, key: &str) -> Option<bool> {
+        self.value(key).map(|v| unsafe { v.to_bool() })
+    }
+    pub fn set_bool(&mut self, key: &str, value: bool) {
+        self.set_value(key, unsafe { QVariant::from_bool(value) });
+    }
+    pub fn bytes(&self, key: &str) -> Option<Vec<u8>> {
+        self.value(key).map(|v| unsafe {
+            let bytes = v.to_byte_array();
+            std::slice::from_raw_parts(bytes.const_data() as *const u8, bytes.count() as usize).to_vec()
+        })
+    }
+    pub fn set_bytes(&mut self, key: &str, value: &[u8]) {
+        self.set_value(key, unsafe { QVariant::from_q_byte_array(&QByteArray::from_slice(value)) });
+    }
+    pub fn list(&self, key: &str) -> Option<Vec<String>> {
+        self.value(key).map(|v| unsafe { qstring_list_to_vec(&v.to_string_list()) })
+    }
+    pub fn set_list<S: AsRef<str>>(&mut self, key: &str, value: &[S]) {
+        let list = unsafe { QStringList::new() };
+        for item in value {
+            unsafe { list.append_q_string(&QString::from_std_str(item.as_ref())) };
+        }
+        self.set_value(key, unsafe { QVariant::from_q_string_list(&list) });
+    }
+
+    fn value(&self, key: &str) -> Option<CppBox<QVariant>> {
+        let key = QString::from_std_str(key);
+        unsafe {
+            if self.settings.contains(&key) {
+                Some(self.settings.value_1a(&key))
+            } else {
+                None
+            }
+        }
+    }
+    fn set_value(&mut self, key: &str, value: CppBox<QVariant>) {
+        unsafe { self.settings.set_value(&QString::from_std_str(key), &value) };
+        self.changed(key);
+    }
+    fn changed(&mut self, key: &str) {
+        if let Some(ref mut cb) = self.on_change {
+            let group = unsafe { self.settings.group().to_std_string() };
+            if group.is_empty() {
+                (cb.as_mut())(key);
+            } else {
+                (cb.as_mut())(&format!("{}/{}", group, key));
+            }
+        }
+    }
+}
@@ -0,0 +1,283 @@
+use crate::common::{self, *};
+
+use qt_core::ShortcutContext;
+use qt_gui::q_key_sequence::{SequenceFormat, StandardKey as QStandardKey};
+use qt_gui::QKeySequence;
+use qt_widgets::{QAction, QApplication};
+
+use std::collections::HashMap;
+
+/// Platform-independent key bindings, mapped by Qt to the platform convention, e.g. `Ctrl+S` or `Cmd+S` for `Save`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StandardKey {
+    New,
+    Open,
+    Save,
+    SaveAs,
+    Close,
+    Quit,
+    Print,
+    Undo,
+    Redo,
+    Cut,
+    Copy,
+    Paste,
+    Delete,
+    SelectAll,
+    Find,
+    FindNext,
+    FindPrevious,
+    Replace,
+    Refresh,
+    Preferences,
+    HelpContents,
+    ZoomIn,
+    ZoomOut,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum KeySequence {
+    /// A sequence in Qt portable text, e.g. `"Ctrl+S"` or `"Ctrl+K, Ctrl+C"`.
+    Text(String),
+    Standard(StandardKey),
+}
+
+/// Where a shortcut is active. Window and control scopes are given by the id of a member.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShortcutScope {
+    /// Active while any window of the application is.
+    Application,
+    /// Active while the window holding the member is.
+    Window(ids::Id),
+    /// Active while the control or one of its children has the focus.
+    Control(ids::Id),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ShortcutHandle(usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShortcutError {
+    /// The key sequence text could not be parsed, or the platform has no binding for the standard key.
+    InvalidKeys,
+    /// No member with the id of the scope.
+    NotFound,
+    /// The keys are already bound by a shortcut that can be active at the same time, Qt would trigger neither.
+    Conflict(ShortcutHandle),
+}
+
+struct Shortcut {
+    sequences: Vec<String>,
+    // `None` for the application scope
+    scope: Option<QPtr<QWidget>>,
+    action: QBox<QAction>,
+    _triggered: QBox<SlotNoArgs>,
+    callback: Option<callbacks::Action>,
+    menu_items: Vec<QPtr<QAction>>,
+}
+
+/// Keyboard shortcuts of the application, each backed by a `QAction` added to the widgets of its scope.
+#[derive(Default)]
+pub struct Shortcuts {
+    next: usize,
+    shortcuts: HashMap<usize, Shortcut>,
+}
+
+impl Shortcuts {
+    pub(crate) fn add(&mut self, keys: &KeySequence, scope: ShortcutScope, scope_widget: Option<QPtr<QWidget>>, windows: &[Ptr<QWidget>], callback: callbacks::Action) -> Result<ShortcutHandle, ShortcutError> {
+        let sequences = sequences(keys)?;
+        if let Some(conflict) = self.conflict(None, &sequences, scope_widget.as_ref()) {
+            return Err(ShortcutError::Conflict(conflict));
+        }
+        let id = self.next;
+        self.next += 1;
+        let triggered = unsafe { SlotNoArgs::new(NullPtr, move || common::catch_panic((), || fire(id))) };
+        let action = unsafe { QAction::new() };
+        unsafe {
+            set_keys(&action, keys);
+            action.set_shortcut_context(match scope {
+                ShortcutScope::Application => ShortcutContext::ApplicationShortcut,
+                ShortcutScope::Window(_) => ShortcutContext::WindowShortcut,
+                ShortcutScope::Control(_) => ShortcutContext::WidgetWithChildrenShortcut,
+            });
+            action.triggered().connect(&triggered);
+            // an action only reacts to its shortcut while one of the widgets it is added to is visible,
+            // so application shortcuts go to every window
+            match scope_widget {
+                Some(ref widget) => widget.add_action(action.as_ptr()),
+                None => {
+                    for window in windows {
+                        window.add_action(action.as_ptr());
+                    }
+                }
+            }
+        }
+        self.shortcuts.insert(id, Shortcut {
+            sequences: sequences,
+            scope: scope_widget,
+            action: action,
+            _triggered: triggered,
+            callback: Some(callback),
+            menu_items: Vec::new(),
+        });
+        Ok(ShortcutHandle(id))
+    }
+    pub(crate) fn rebind(&mut self, handle: ShortcutHandle, keys: &KeySequence) -> Result<(), ShortcutError> {
+        let sequences = sequences(keys)?;
+        let scope = match self.shortcuts.get(&handle.0) {
+            Some(shortcut) => shortcut.scope.clone(),
+            None => return Err(ShortcutError::NotFound),
+        };
+        if let Some(conflict) = self.conflict(Some(handle.0), &sequences, scope.as_ref()) {
+            return Err(ShortcutError::Conflict(conflict));
+        }
+        let shortcut = self.shortcuts.get_mut(&handle.0).unwrap();
+        shortcut.sequences = sequences;
+        unsafe { set_keys(&shortcut.action, keys) };
+        shortcut.update_menu_items();
+        Ok(())
+    }
+    pub(crate) fn remove(&mut self, handle: ShortcutHandle) -> bool {
+        match self.shortcuts.remove(&handle.0) {
+            Some(Shortcut { action, _triggered, menu_items, .. }) => unsafe {
+                for item in menu_items.iter().filter(|item| !item.is_null()) {
+                    common::set_action_shortcut_text(item.as_ptr(), None);
+                }
+                // the shortcut may be the one currently firing
+                action.into_ptr().delete_later();
+                _triggered.into_ptr().delete_later();
+                true
+            },
+            None => false,
+        }
+    }
+    /// The shortcut as shown to the user on this platform, e.g. `Ctrl+S`.
+    pub(crate) fn text(&self, handle: ShortcutHandle) -> Option<String> {
+        self.shortcuts.get(&handle.0).map(|shortcut| unsafe { shortcut.action.shortcut().to_string_1a(SequenceFormat::NativeText).to_std_string() })
+    }
+    pub(crate) fn show_in_menu(&mut self, handle: ShortcutHandle, item: Ptr<QAction>) -> bool {
+        match self.shortcuts.get_mut(&handle.0) {
+            Some(shortcut) => {
+                shortcut.menu_items.push(unsafe { QPtr::new(item) });
+                shortcut.update_menu_items();
+                true
+            }
+            None => false,
+        }
+    }
+    /// Adds the application shortcuts to a window created after them.
+    pub(crate) fn attach_window(&self, window: Ptr<QWidget>) {
+        for shortcut in self.shortcuts.values().filter(|shortcut| shortcut.scope.is_none()) {
+            unsafe { window.add_action(shortcut.action.as_ptr()) };
+        }
+    }
+
+    fn conflict(&self, skip: Option<usize>, sequences: &[String], scope: Option<&QPtr<QWidget>>) -> Option<ShortcutHandle> {
+        self.shortcuts
+            .iter()
+            .filter(|(id, _)| Some(**id) != skip)
+            .filter(|(_, shortcut)| shortcut.sequences.iter().any(|sequence| sequences.contains(sequence)))
+            .find(|(_, shortcut)| match (shortcut.scope.as_ref(), scope) {
+                (None, _) | (_, None) => true,
+                (Some(a), Some(b)) => unsafe {
+                    !a.is_null() && !b.is_null() && (a.as_raw_ptr() == b.as_raw_ptr() || a.is_ancestor_of(b.as_ptr()) || b.is_ancestor_of(a.as_ptr()))
+                },
+            })
+            .map(|(id, _)| ShortcutHandle(*id))
+    }
+}
+
+impl Shortcut {
+    fn update_menu_items(&mut self) {
+        self.menu_items.retain(|item| unsafe { !item.is_null() });
+        let text = unsafe { self.action.shortcut().to_string_1a(SequenceFormat::NativeText).to_std_string() };
+        for item in self.menu_items.iter() {
+            common::set_action_shortcut_text(unsafe { item.as_ptr() }, Some(text.as_str()));
+        }
+    }
+}
+
+fn fire(id: usize) {
+    let app = match crate::application::application() {
+        Some(app) => app,
+        None => return,
+    };
+    let app2 = app as *mut crate::application::Application;
+    let (scope, callback) = match app.inner_mut().shortcuts.shortcuts.get_mut(&id) {
+        Some(shortcut) => (shortcut.scope.clone(), shortcut.callback.take()),
+        None => return,
+    };
+    let mut callback = match callback {
+        Some(callback) => callback,
+        None => return,
+    };
+    // application shortcuts report the active window
+    let widget = match scope {
+        Some(widget) => widget,
+        None => unsafe { QApplication::active_window() },
+    };
+    if unsafe { !widget.is_null() } {
+        if let Some(base) = common::cast_qobject_to_base_mut(unsafe { &widget.static_upcast::<QObject>() }) {
+            (callback.as_mut())(base.as_member_mut());
+        }
+    }
+    if let Some(shortcut) = unsafe { &mut *app2 }.inner_mut().shortcuts.shortcuts.get_mut(&id) {
+        shortcut.callback = Some(callback);
+    }
+}
+fn sequences(keys: &KeySequence) -> Result<Vec<String>, ShortcutError> {
+    unsafe {
+        match keys {
+            KeySequence::Text(text) => {
+                let sequence = QKeySequence::from_q_string_sequence_format(&QString::from_std_str(text), SequenceFormat::PortableText);
+                if sequence.is_empty() {
+                    Err(ShortcutError::InvalidKeys)
+                } else {
+                    Ok(vec![sequence.to_string_1a(SequenceFormat::PortableText).to_std_string()])
+                }
+            }
+            KeySequence::Standard(key) => {
+                let bindings = QKeySequence::key_bindings(standard_key_to_qt(*key));
+                // the platform may have no binding for the key at all
+                if bindings.is_empty() {
+                    Err(ShortcutError::InvalidKeys)
+                } else {
+                    Ok((0..bindings.size()).map(|i| bindings.at(i).to_string_1a(SequenceFormat::PortableText).to_std_string()).collect())
+                }
+            }
+        }
+    }
+}
+unsafe fn set_keys(action: &QAction, keys: &KeySequence) {
+    match keys {
+        KeySequence::Text(text) => action.set_shortcut(&QKeySequence::from_q_string_sequence_format(&QString::from_std_str(text), SequenceFormat::PortableText)),
+        KeySequence::Standard(key) => action.set_shortcuts_standard_key(standard_key_to_qt(*key)),
+    }
+}
+fn standard_key_to_qt(key: StandardKey) -> QStandardKey {
+    match key {
+        StandardKey::New => QStandardKey::New,
+        StandardKey::Open => QStandardKey::Open,
+        StandardKey::Save => QStandardKey::Save,
+        StandardKey::SaveAs => QStandardKey::SaveAs,
+        StandardKey::Close => QStandardKey::Close,
+        StandardKey::Quit => QStandardKey::Quit,
+        StandardKey::Print => QStandardKey::Print,
+        StandardKey::Undo => QStandardKey::Undo,
+        StandardKey::Redo => QStandardKey::Redo,
+        StandardKey::Cut => QStandardKey::Cut,
+        StandardKey::Copy => QStandardKey::Copy,
+        StandardKey::Paste => QStandardKey::Paste,
+        StandardKey::Delete => QStandardKey::Delete,
+        StandardKey::SelectAll => QStandardKey::SelectAll,
+        StandardKey::Find => QStandardKey::Find,
+        StandardKey::FindNext => QStandardKey::FindNext,
+        StandardKey::FindPrevious => QStandardKey::FindPrevious,
+        StandardKey::Replace => QStandardKey::Replace,
+        StandardKey::Refresh => QStandardKey::Refresh,
+        StandardKey::Preferences => QStandardKey::Preferences,
+        StandardKey::HelpContents => QStandardKey::HelpContents,
+        StandardKey::ZoomIn => QStandardKey::ZoomIn,
+        StandardKey::ZoomOut => QStandardKey::ZoomOut,
+    }
+}
@@ -0,0 +1,118 @@
+use crate::common::{self, *};
+
+use std::any::Any;
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum SnapshotSize {
+    MatchParent,
+    WrapContent,
+    Exact(u16),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct SnapshotNode {
+    pub member_type: &'static str,
+    pub id: String,
+    pub tag: Option<String>,
+    pub label: Option<String>,
+    pub visible: bool,
+    pub size: (u16, u16),
+    pub coords: (i32, i32),
+    pub layout: Option<(SnapshotSize, SnapshotSize)>,
+    pub children: Vec<SnapshotNode>,
+}
+
+impl SnapshotNode {
+    pub fn from_member(member: &dyn controls::Member) -> SnapshotNode {
+        // trays are backed by a QSystemTrayIcon, which has no widget geometry
+        let (visible, size, coords) = if member.as_any().is::<crate::tray::Tray>() {
+            (member.is_has_visibility().map(|v| v.visibility() == types::Visibility::Visible).unwrap_or(true), (0, 0), (0, 0))
+        } else {
+            let widget = common::cast_member_to_qwidget(member);
+            unsafe {
+                let size = widget.size();
+                let pos = widget.pos();
+                (widget.is_visible(), (utils::coord_to_size(size.width()), utils::coord_to_size(size.height())), (pos.x(), pos.y()))
+            }
+        };
+        let mut node = SnapshotNode {
+            member_type: member_type(member.as_any()),
+            id: format!("{:?}", member.id()),
+            tag: member.tag().map(|tag| tag.into_owned()),
+            label: member.is_has_label().map(|l| l.label().into_owned()),
+            visible: visible,
+            size: size,
+            coords: coords,
+            layout: None,
+            children: Vec::new(),
+        };
+        if let Some(control) = member.is_control() {
+            node.size = control.size();
+            node.layout = Some((size_to_snapshot(control.layout_width()), size_to_snapshot(control.layout_height())));
+        }
+        if let Some(container) = member.is_container() {
+            if let Some(single) = container.is_single_container() {
+                if let Some(child) = single.child() {
+                    node.children.push(SnapshotNode::from_member(child.as_member()));
+                }
+            } else if let Some(multi) = container.is_multi_container() {
+                for i in 0..multi.len() {
+                    if let Some(child) = multi.child_at(i) {
+                        node.children.push(SnapshotNode::from_member(child.as_member()));
+                    }
+                }
+            }
+        }
+        node
+    }
+    /// Depth-first search of the subtree by tag, including the node itself.
+    pub fn find_by_tag(&self, tag: &str) -> Option<&SnapshotNode> {
+        if self.tag.as_ref().map(|t| t.as_str()) == Some(tag) {
+            return Some(self);
+        }
+        self.children.iter().filter_map(|child| child.find_by_tag(tag)).next()
+    }
+}
+
+fn size_to_snapshot(size: layout::Size) -> SnapshotSize {
+    match size {
+        layout::Size::MatchParent => SnapshotSize::MatchParent,
+        layout::Size::WrapContent => SnapshotSize::WrapContent,
+        layout::Size::Exact(value) => SnapshotSize::Exact(value),
+    }
+}
+
+fn member_type(member: &dyn Any) -> &'static str {
+    if member.is::<crate::window::Window>() {
+        "Window"
+    } else if member.is::<crate::tray::Tray>() {
+        "Tray"
+    } else if member.is::<crate::button::Button>() {
+        "Button"
+    } else if member.is::<crate::text::Text>() {
+        "Text"
+    } else if member.is::<crate::image::Image>() {
+        "Image"
+    } else if member.is::<crate::frame::Frame>() {
+        "Frame"
+    } else if member.is::<crate::layout_linear::LinearLayout>() {
+        "LinearLayout"
+    } else if member.is::<crate::splitted::Splitted>() {
+        "Splitted"
+    } else if member.is::<crate::progress_bar::ProgressBar>() {
+        "ProgressBar"
+    } else if member.is::<crate::list::List>() {
+        "List"
+    } else if member.is::<crate::tree::Tree>() {
+        "Tree"
+    } else if member.is::<crate::message::Message>() {
+        "Message"
+    } else {
+        "Unknown"
+    }
+}
@@ -34,7 +34,7 @@ impl<O: controls::Splitted> NewSplittedInner<O> for QtSplitted {
             let ptr = ptr as *const _ as u64;
             ll.base.widget.set_children_collapsible(false);
             ll.splitter_moved.set(move |position| {
-                splitter_moved(&mut *(ptr as *mut Splitted), position);
+                common::catch_panic((), || splitter_moved(&mut *(ptr as *mut Splitted), position));
             });
             ll.base.widget.splitter_moved().connect(&ll.splitter_moved);
             {
@@ -0,0 +1,96 @@
+use crate::common::{self, *};
+
+use qt_core::{QCoreApplication, QFileSystemWatcher, SlotOfQString};
+use qt_widgets::QApplication;
+
+use std::{fs, io, path};
+
+/// Re-applies an application stylesheet whenever its file changes on disk. Meant for development, dropping it stops watching.
+pub struct StyleWatcher {
+    watcher: QBox<QFileSystemWatcher>,
+    _changed: QBox<SlotOfQString>,
+}
+
+impl StyleWatcher {
+    pub(crate) fn new(path: &path::Path) -> io::Result<Self> {
+        let path = fs::canonicalize(path)?;
+        let qpath = QString::from_std_str(path.to_string_lossy());
+        let watcher = unsafe { QFileSystemWatcher::new_0a() };
+        let wptr = unsafe { watcher.as_ptr() };
+        let changed = unsafe {
+            SlotOfQString::new(NullPtr, move |_| common::catch_panic((), || {
+                // editors often save by replacing the file, which drops it from the watch list
+                if !wptr.files().contains_q_string(&QString::from_std_str(path.to_string_lossy())) {
+                    wptr.add_path(&QString::from_std_str(path.to_string_lossy()));
+                }
+                if let Ok(css) = fs::read_to_string(&path) {
+                    set_application_stylesheet(&css);
+                }
+            }))
+        };
+        unsafe {
+            watcher.add_path(&qpath);
+            watcher.file_changed().connect(&changed);
+        }
+        Ok(StyleWatcher { watcher: watcher, _changed: changed })
+    }
+}
+
+impl Drop for StyleWatcher {
+    fn drop(&mut self) {
+        unsafe { self.watcher.remove_paths(&self.watcher.files()) };
+    }
+}
+
+/// Applies `css` to every window of the application. Tagged members can be targeted with `#tag` selectors.
+pub fn set_application_stylesheet(css: &str) {
+    unsafe {
+        if let Some(app) = crate::application::application() {
+            use plygui_api::controls::Application;
+            for root in app.roots_mut() {
+                sync_object_names(root);
+            }
+        }
+        let app = QCoreApplication::instance().dynamic_cast::<QApplication>();
+        app.set_style_sheet(&QString::from_std_str(css));
+    }
+}
+/// Applies `css` to the member and its children only. Tagged members can be targeted with `#tag` selectors.
+pub fn set_member_stylesheet(member: &mut dyn controls::Member, css: &str) {
+    if !common::is_widget_member(member) {
+        return;
+    }
+    sync_object_names(member);
+    unsafe { common::cast_member_to_qwidget_mut(member).set_style_sheet(&QString::from_std_str(css)) };
+}
+pub fn member_stylesheet(member: &dyn controls::Member) -> String {
+    if !common::is_widget_member(member) {
+        return String::new();
+    }
+    unsafe { common::cast_member_to_qwidget(member).style_sheet().to_std_string() }
+}
+/// Sets `objectName` of the member and its children to their plygui tag, so stylesheets can select them as `#tag`.
+/// Controls also do it themselves whenever they are drawn, e.g. when added to a container. Call it directly for windows,
+/// or for controls re-tagged after they were laid out.
+pub fn sync_object_names(member: &mut dyn controls::Member) {
+    common::for_each_member_mut(member, &mut |member| {
+        if common::is_widget_member(member) {
+            sync_object_name(member);
+        }
+    });
+}
+pub(crate) fn sync_object_name(member: &dyn controls::Member) {
+    let tag = match member.tag() {
+        Some(tag) => QString::from_std_str(tag),
+        None => return,
+    };
+    unsafe {
+        let widget = Ptr::from_raw(member.native_id() as *const QWidget);
+        if widget.object_name().compare_q_string(&tag) != 0 {
+            widget.set_object_name(&tag);
+            // selectors are matched when a widget is polished, so a re-tagged one needs it again
+            widget.style().unpolish_q_widget(widget);
+            widget.style().polish_q_widget(widget);
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use crate::common::{self, *};
+
+use qt_core::QTimer;
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+pub type OnTimer = Box<dyn FnMut(&mut dyn controls::Application)>;
+
+/// Identifies a timeout or interval set on the application. Stays valid until it is cancelled or, for a timeout, fired.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TimerHandle(usize);
+
+struct Timer {
+    timer: QBox<QTimer>,
+    _timeout: QBox<SlotNoArgs>,
+    callback: Option<OnTimer>,
+}
+
+/// Timers of the application, each backed by its own `QTimer` and fired on the UI thread.
+#[derive(Default)]
+pub struct Timers {
+    next: usize,
+    timers: HashMap<usize, Timer>,
+}
+
+impl Timers {
+    pub(crate) fn add(&mut self, duration: Duration, repeat: bool, callback: OnTimer) -> TimerHandle {
+        let id = self.next;
+        self.next += 1;
+        let timeout = unsafe { SlotNoArgs::new(NullPtr, move || common::catch_panic((), || fire(id))) };
+        let timer = unsafe { QTimer::new_0a() };
+        unsafe {
+            timer.set_single_shot(!repeat);
+            timer.set_interval(duration_to_ms(duration));
+            timer.timeout().connect(&timeout);
+            timer.start_0a();
+        }
+        self.timers.insert(id, Timer { timer: timer, _timeout: timeout, callback: Some(callback) });
+        TimerHandle(id)
+    }
+    pub(crate) fn cancel(&mut self, handle: TimerHandle) -> bool {
+        match self.timers.remove(&handle.0) {
+            Some(Timer { timer, _timeout, .. }) => unsafe {
+                timer.stop();
+                // the timer may be the one currently firing, so neither it nor its slot can be deleted right away
+                timer.into_ptr().delete_later();
+                _timeout.into_ptr().delete_later();
+                true
+            },
+            None => false,
+        }
+    }
+    /// Restarts the timer with a new duration, counting from now.
+    pub(crate) fn reschedule(&mut self, handle: TimerHandle, duration: Duration) -> bool {
+        match self.timers.get(&handle.0) {
+            Some(timer) => {
+                unsafe { timer.timer.start_1a(duration_to_ms(duration)) };
+                true
+            }
+            None => false,
+        }
+    }
+    pub(crate) fn is_active(&self, handle: TimerHandle) -> bool {
+        self.timers.get(&handle.0).map(|timer| unsafe { timer.timer.is_active() }).unwrap_or(false)
+    }
+}
+
+impl Drop for Timers {
+    fn drop(&mut self) {
+        for (_, timer) in self.timers.iter() {
+            unsafe { timer.timer.stop() };
+        }
+    }
+}
+
+fn fire(id: usize) {
+    let app = match crate::application::application() {
+        Some(app) => app,
+        None => return,
+    };
+    let app2 = app as *mut crate::application::Application;
+    let (callback, repeat) = match app.inner_mut().timers.timers.get_mut(&id) {
+        Some(timer) => (timer.callback.take(), unsafe { !timer.timer.is_single_shot() }),
+        None => return,
+    };
+    if let Some(mut callback) = callback {
+        (callback.as_mut())(unsafe { &mut *app2 });
+        if repeat {
+            if let Some(timer) = app.inner_mut().timers.timers.get_mut(&id) {
+                timer.callback = Some(callback);
+            }
+        }
+    }
+    if !repeat {
+        app.inner_mut().timers.cancel(TimerHandle(id));
+    }
+}
+fn duration_to_ms(duration: Duration) -> i32 {
+    cmp::min(duration.as_millis(), i32::max_value() as u128) as i32
+}
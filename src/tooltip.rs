@@ -0,0 +1,105 @@
+use crate::common::{self, *};
+
+use qt_widgets::{QListWidget, QTreeWidget, QTreeWidgetItem};
+
+/// Tooltips of controls. The text may be rich text (HTML), Qt detects it by the markup.
+pub trait Tooltip {
+    /// Sets the tooltip, `None` removes it.
+    fn set_tooltip(&mut self, tooltip: Option<&str>);
+    fn tooltip(&self) -> Option<String>;
+}
+
+impl<T: controls::Control + ?Sized> Tooltip for T {
+    fn set_tooltip(&mut self, tooltip: Option<&str>) {
+        let widget = common::cast_member_to_qwidget_mut(self.as_member_mut());
+        unsafe { widget.set_tool_tip(&QString::from_std_str(tooltip.unwrap_or(""))) };
+    }
+    fn tooltip(&self) -> Option<String> {
+        let widget = common::cast_member_to_qwidget(self.as_member());
+        non_empty(unsafe { widget.tool_tip().to_std_string() })
+    }
+}
+
+/// Tooltips of adapter items in `List` and `Tree`, addressed the same way as in `on_item_click`:
+/// `[i]` for a list row, `[i, j, ...]` for a tree node. The tooltip is kept until the item is removed from the adapter.
+pub trait ItemTooltip {
+    /// Returns `false` if there is no item at `indexes`.
+    fn set_item_tooltip(&mut self, indexes: &[usize], tooltip: Option<&str>) -> bool;
+    fn item_tooltip(&self, indexes: &[usize]) -> Option<String>;
+}
+
+impl ItemTooltip for dyn controls::List {
+    fn set_item_tooltip(&mut self, indexes: &[usize], tooltip: Option<&str>) -> bool {
+        set_item_tooltip(self.as_member(), indexes, tooltip)
+    }
+    fn item_tooltip(&self, indexes: &[usize]) -> Option<String> {
+        item_tooltip(self.as_member(), indexes)
+    }
+}
+impl ItemTooltip for dyn controls::Tree {
+    fn set_item_tooltip(&mut self, indexes: &[usize], tooltip: Option<&str>) -> bool {
+        set_item_tooltip(self.as_member(), indexes, tooltip)
+    }
+    fn item_tooltip(&self, indexes: &[usize]) -> Option<String> {
+        item_tooltip(self.as_member(), indexes)
+    }
+}
+
+fn set_item_tooltip(member: &dyn controls::Member, indexes: &[usize], tooltip: Option<&str>) -> bool {
+    let tooltip = QString::from_std_str(tooltip.unwrap_or(""));
+    unsafe {
+        let object = Ptr::from_raw(member.native_id() as *const QObject);
+        let list = object.dynamic_cast::<QListWidget>();
+        if !list.is_null() {
+            return match indexes {
+                [i] if *i < list.count() as usize => {
+                    list.item(*i as i32).set_tool_tip(&tooltip);
+                    true
+                }
+                _ => false,
+            };
+        }
+        match tree_item(object, indexes) {
+            Some(item) => {
+                item.set_tool_tip(0, &tooltip);
+                true
+            }
+            None => false,
+        }
+    }
+}
+fn item_tooltip(member: &dyn controls::Member, indexes: &[usize]) -> Option<String> {
+    unsafe {
+        let object = Ptr::from_raw(member.native_id() as *const QObject);
+        let list = object.dynamic_cast::<QListWidget>();
+        if !list.is_null() {
+            return match indexes {
+                [i] if *i < list.count() as usize => non_empty(list.item(*i as i32).tool_tip().to_std_string()),
+                _ => None,
+            };
+        }
+        tree_item(object, indexes).and_then(|item| non_empty(item.tool_tip(0).to_std_string()))
+    }
+}
+
+unsafe fn tree_item(object: Ptr<QObject>, indexes: &[usize]) -> Option<Ptr<QTreeWidgetItem>> {
+    let tree = object.dynamic_cast::<QTreeWidget>();
+    if tree.is_null() || indexes.is_empty() || indexes[0] >= tree.top_level_item_count() as usize {
+        return None;
+    }
+    let mut item = tree.top_level_item(indexes[0] as i32);
+    for i in &indexes[1..] {
+        if *i >= item.child_count() as usize {
+            return None;
+        }
+        item = item.child(*i as i32);
+    }
+    Some(item)
+}
+fn non_empty(text: String) -> Option<String> {
+    if text.is_empty() {
+        None
+    } else {
+        Some(text)
+    }
+}
@@ -13,6 +13,7 @@ pub type Tray = AMember<ACloseable<ATray<QtTray>>>;
 pub struct QtTray {
     tray: QBox<QSystemTrayIcon>,
     filter: QBox<CustomEventFilter>,
+    _events: Box<EventCallbacks>,
     menu: Option<(QBox<QMenu>, Vec<(callbacks::Action, QBox<SlotNoArgs>)>)>,
     on_close: Option<callbacks::OnClose>,
     skip_callbacks: bool,
@@ -81,8 +82,9 @@ impl<O: controls::Tray> NewTrayInner<O> for QtTray {
         let qicon = unsafe { QApplication::style().standard_icon_1a(StandardPixmap::SPDesktopIcon) };
         let tray = unsafe { QSystemTrayIcon::new() };
         let mut t = QtTray {
+            _events: EventCallbacks::attached_to(unsafe { &tray.static_upcast::<QObject>() }),
             tray: tray,
-            filter: CustomEventFilter::new(event_handler),
+            filter: CustomEventFilter::new(|object, event| common::catch_panic(false, || event_handler(object, event))),
             menu: None,
             on_close: None,
             skip_callbacks: false,
@@ -109,7 +111,7 @@ impl<O: controls::Tray> NewTrayInner<O> for QtTray {
                                 }
                             }
                         };
-                        unsafe { SlotNoArgs::new(NullPtr, handler) }
+                        unsafe { SlotNoArgs::new(NullPtr, move || common::catch_panic((), &handler)) }
                     }
 
                     common::make_menu(context_menu, items, storage, slot_spawn, selfptr);
@@ -183,7 +185,7 @@ fn event_handler(object: &mut QObject, event: &mut QEvent) -> bool {
                 }
                 let id = w.id();
                 let app = w.inner_mut().application_impl_mut::<crate::application::Application>();
-                let _ = app.base.sender().send((move |a: &mut dyn controls::Application| {
+                let _ = app.inner().sender().send((move |a: &mut dyn controls::Application| {
                     a.as_any_mut().downcast_mut::<crate::application::Application>().unwrap().base.windows.retain(|w| w.id() != id);
                     false
                 }).into());
@@ -29,6 +29,9 @@ pub struct QtTree {
 }
 impl ItemClickableInner for QtTree {
     fn item_click(&mut self, i: &[usize], item_view: &mut dyn controls::Control, _skip_callbacks: bool) {
+        if !unsafe { self.base.widget.is_enabled() } {
+            return;
+        }
         let this = common::cast_qobject_to_uimember_mut::<Tree>(&mut self.base.as_qwidget()).unwrap();
         if let Some(ref mut callback) = self.h_left_clicked.0 {
             (callback.as_mut())(this, i, item_view)
@@ -185,7 +188,7 @@ impl<O: controls::Tree> NewTreeInner<O> for QtTree {
         unsafe {
             let ptr = ptr as *const _ as u64;
             let obj = ll.base.widget.static_upcast::<QObject>().as_mut_raw_ptr();
-            ll.h_left_clicked.1 = SlotNoArgs::new(NullPtr, move || {
+            ll.h_left_clicked.1 = SlotNoArgs::new(NullPtr, move || common::catch_panic((), || {
                 let this = cast_qobject_to_uimember_mut::<Tree>(&mut *obj).unwrap();
                 let mut clicked = this.inner().inner().inner().inner().inner().base.widget.current_item();
                 let clicked_widget = this.inner_mut().inner_mut().inner_mut().inner_mut().inner_mut().base.widget.item_widget(clicked, 0);
@@ -210,7 +213,7 @@ impl<O: controls::Tree> NewTreeInner<O> for QtTree {
                     let clicked = cast_qobject_to_base_mut(&clicked_widget.static_upcast::<QObject>().as_ref().unwrap()).unwrap();
                     (cb.as_mut())(this, indexes.as_slice(), clicked.as_member_mut().is_control_mut().unwrap());
                 }
-            });
+            }));
             ll.base.widget.set_header_hidden(true);
             ll.base.widget.item_clicked().connect(&ll.h_left_clicked.1);
             let qo = ll.base.widget.static_upcast::<QObject>();
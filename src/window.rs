@@ -1,10 +1,13 @@
 use crate::common::{self, *};
 
-use qt_widgets::QApplication;
 use qt_widgets::QMainWindow;
 
 use std::borrow::Cow;
 
+// size of a `Fullscreen` window when there is no screen to take it from
+const FALLBACK_WIDTH: i32 = 800;
+const FALLBACK_HEIGHT: i32 = 600;
+
 pub type Window = AMember<AContainer<ASingleContainer<ACloseable<AWindow<QtWindow>>>>>;
 
 #[repr(C)]
@@ -12,6 +15,7 @@ pub struct QtWindow {
     window: QBox<QMainWindow>,
     child: Option<Box<dyn controls::Control>>,
     filter: QBox<CustomEventFilter>,
+    _events: Box<EventCallbacks>,
     menu: Vec<(callbacks::Action, QBox<SlotNoArgs>)>,
     on_close: Option<callbacks::OnClose>,
     skip_callbacks: bool,
@@ -48,10 +52,12 @@ impl CloseableInner for QtWindow {
 impl<O: controls::Window> NewWindowInner<O> for QtWindow {
     fn with_uninit_params(u: &mut mem::MaybeUninit<O>, app: &mut dyn controls::Application, title: &str, start_size: types::WindowStartSize, menu: types::Menu) -> Self {
    		let selfptr = u as *mut _ as *mut Window;
+        let window = unsafe { QMainWindow::new_0a() };
    		let mut w = QtWindow {
-            window: unsafe { QMainWindow::new_0a() },
+            _events: EventCallbacks::attached_to(unsafe { &window.static_upcast::<QObject>() }),
+            window: window,
             child: None,
-            filter: CustomEventFilter::new(event_handler::<Window>),
+            filter: CustomEventFilter::new(|object, event| common::catch_panic(false, || event_handler::<Window>(object, event))),
             menu: if menu.is_some() { Vec::new() } else { Vec::with_capacity(0) },
             on_close: None,
             skip_callbacks: false,
@@ -61,10 +67,11 @@ impl<O: controls::Window> NewWindowInner<O> for QtWindow {
             w.window.set_window_title(&QString::from_std_str(&title));
             let (ww, hh) = match start_size {
                 types::WindowStartSize::Exact(w, h) => (w as i32, h as i32),
-                types::WindowStartSize::Fullscreen => {
-                    let screen = QApplication::desktop().screen_geometry();
-                    (screen.width(), screen.height())
-                }
+                // there is no primary screen while no output is connected
+                types::WindowStartSize::Fullscreen => match crate::screen::primary_screen() {
+                    Some(screen) => (screen.geometry.width, screen.geometry.height),
+                    None => (FALLBACK_WIDTH, FALLBACK_HEIGHT),
+                },
             };
             w.window.resize_2a(ww, hh);
             w.window.set_size_policy_2a(QSizePolicy::Ignored, QSizePolicy::Ignored);
@@ -85,7 +92,7 @@ impl<O: controls::Window> NewWindowInner<O> for QtWindow {
 	                }
 	            };
 	
-	            unsafe { SlotNoArgs::new(NullPtr, handler) }
+	            unsafe { SlotNoArgs::new(NullPtr, move || common::catch_panic((), &handler)) }
             }
             for item in items.drain(..) {
                 match item {
@@ -111,8 +118,9 @@ impl<O: controls::Window> NewWindowInner<O> for QtWindow {
         w
     }
 }
-impl WindowInner for QtWindow {
-    fn with_params<S: AsRef<str>>(app: &mut dyn controls::Application, title: S, start_size: types::WindowStartSize, menu: types::Menu) -> Box<dyn controls::Window> {
+impl QtWindow {
+    /// Creates the window without showing it.
+    pub(crate) fn create(app: &mut dyn controls::Application, title: &str, start_size: types::WindowStartSize, menu: types::Menu) -> Box<Window> {
         let app = app.as_any_mut().downcast_mut::<crate::application::Application>().unwrap();
         let mut b: Box<mem::MaybeUninit<Window>> = Box::new_uninit();
         let ab = AMember::with_inner(
@@ -120,17 +128,23 @@ impl WindowInner for QtWindow {
 	            ASingleContainer::with_inner(
 	                ACloseable::with_inner(
     	                AWindow::with_inner(
-    	                    <Self as NewWindowInner<Window>>::with_uninit_params(b.as_mut(), app, title.as_ref(), start_size, menu),
+    	                    <Self as NewWindowInner<Window>>::with_uninit_params(b.as_mut(), app, title, start_size, menu),
     	                ),
     	                app
 	                )
 	            )
             ),
         );
-        let mut w = unsafe {
+        let w = unsafe {
 	        b.as_mut_ptr().write(ab);
 	        b.assume_init()
         };
+        w
+    }
+}
+impl WindowInner for QtWindow {
+    fn with_params<S: AsRef<str>>(app: &mut dyn controls::Application, title: S, start_size: types::WindowStartSize, menu: types::Menu) -> Box<dyn controls::Window> {
+        let mut w = QtWindow::create(app, title.as_ref(), start_size, menu);
         unsafe { w.inner_mut().inner_mut().inner_mut().inner_mut().inner_mut().window.show(); }
         w
     }
@@ -234,6 +248,9 @@ impl Drop for QtWindow {
 }
 
 fn event_handler<O: controls::Window>(object: &mut QObject, event: &mut QEvent) -> bool {
+    if common::dispatch_events(object, event) {
+        return true;
+    }
     match unsafe { event.type_() } {
         QEventType::Resize => {
             if let Some(window) = common::cast_qobject_to_uimember_mut::<Window>(object) {
@@ -260,7 +277,7 @@ fn event_handler<O: controls::Window>(object: &mut QObject, event: &mut QEvent)
                 }
                 let id = w.id();
                 let app = w.inner_mut().inner_mut().inner_mut().application_impl_mut::<crate::application::Application>();
-                let _ = app.base.sender().send((move |a: &mut dyn controls::Application| {
+                let _ = app.inner().sender().send((move |a: &mut dyn controls::Application| {
                     let app = a.as_any_mut().downcast_mut::<crate::application::Application>().unwrap();
                     app.base.windows.retain(|w| w.id() != id);
                     app.inner_mut().maybe_exit()
@@ -0,0 +1,131 @@
+use crate::common::{self, *};
+
+use qt_core::WindowState as QWindowState;
+
+pub type OnWindowStateChanged = Box<dyn FnMut(&mut dyn controls::Member, WindowState)>;
+/// Called with the new position of the window frame on the virtual desktop.
+pub type OnWindowMove = Box<dyn FnMut(&mut dyn controls::Member, i32, i32)>;
+/// Called with the new size of the window contents.
+pub type OnWindowResize = Box<dyn FnMut(&mut dyn controls::Member, u16, u16)>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WindowState {
+    Normal,
+    Minimized,
+    Maximized,
+    Fullscreen,
+}
+
+/// Window callbacks, kept in the `EventCallbacks` of the window.
+#[derive(Default)]
+pub struct WindowCallbacks {
+    on_state_changed: Option<OnWindowStateChanged>,
+    on_move: Option<OnWindowMove>,
+    on_resize: Option<OnWindowResize>,
+    // settings key of a window that remembers its geometry
+    pub(crate) geometry_key: Option<String>,
+}
+
+/// Runtime control over the state and placement of windows.
+pub trait WindowControl {
+    /// Switches the state and shows the window. `Normal` restores it from any other state.
+    fn set_window_state(&mut self, state: WindowState);
+    fn window_state(&self) -> WindowState;
+    /// Moves the window frame to the given position on the virtual desktop.
+    fn set_position(&mut self, x: i32, y: i32);
+    fn on_window_state_changed(&mut self, cb: Option<OnWindowStateChanged>);
+    fn on_move(&mut self, cb: Option<OnWindowMove>);
+    fn on_resize(&mut self, cb: Option<OnWindowResize>);
+}
+
+impl<T: controls::Window + ?Sized> WindowControl for T {
+    fn set_window_state(&mut self, state: WindowState) {
+        let widget = common::cast_member_to_qwidget_mut(self.as_member_mut());
+        unsafe {
+            match state {
+                WindowState::Normal => widget.show_normal(),
+                WindowState::Minimized => widget.show_minimized(),
+                WindowState::Maximized => widget.show_maximized(),
+                WindowState::Fullscreen => widget.show_full_screen(),
+            }
+        }
+    }
+    fn window_state(&self) -> WindowState {
+        window_state(common::cast_member_to_qwidget(self.as_member()))
+    }
+    fn set_position(&mut self, x: i32, y: i32) {
+        unsafe { common::cast_member_to_qwidget_mut(self.as_member_mut()).move_2a(x, y) };
+    }
+    fn on_window_state_changed(&mut self, cb: Option<OnWindowStateChanged>) {
+        set(self.as_member_mut(), |window| window.on_state_changed = cb);
+    }
+    fn on_move(&mut self, cb: Option<OnWindowMove>) {
+        set(self.as_member_mut(), |window| window.on_move = cb);
+    }
+    fn on_resize(&mut self, cb: Option<OnWindowResize>) {
+        set(self.as_member_mut(), |window| window.on_resize = cb);
+    }
+}
+
+fn set<F: FnOnce(&mut WindowCallbacks)>(member: &mut dyn controls::Member, f: F) {
+    if let Some(events) = common::member_event_callbacks_mut(member) {
+        f(&mut events.window);
+    }
+}
+
+pub(crate) fn window_state(widget: &QWidget) -> WindowState {
+    let state = unsafe { widget.window_state() };
+    // a minimized window keeps its maximized or fullscreen flag for when it is restored
+    if state.test_flag(QWindowState::WindowMinimized) {
+        WindowState::Minimized
+    } else if state.test_flag(QWindowState::WindowFullScreen) {
+        WindowState::Fullscreen
+    } else if state.test_flag(QWindowState::WindowMaximized) {
+        WindowState::Maximized
+    } else {
+        WindowState::Normal
+    }
+}
+
+/// Runs the window callbacks of `object` for state, move, resize and close events. Never consumes the event.
+pub(crate) fn dispatch(object: &mut QObject, event: &mut QEvent) {
+    let events = match common::event_callbacks_mut(object) {
+        Some(events) => events,
+        None => return,
+    };
+    let member = match common::cast_qobject_to_base_mut(object) {
+        Some(base) => base.as_member_mut(),
+        None => return,
+    };
+    let widget = unsafe { Ptr::from_raw(object as *const QObject).dynamic_cast::<QWidget>() };
+    if unsafe { widget.is_null() || !widget.is_window() } {
+        return;
+    }
+    unsafe {
+        match event.type_() {
+            QEventType::WindowStateChange => {
+                if let Some(ref mut cb) = events.window.on_state_changed {
+                    (cb.as_mut())(member, window_state(&widget));
+                }
+            }
+            QEventType::Move => {
+                if let Some(ref mut cb) = events.window.on_move {
+                    let pos = widget.pos();
+                    (cb.as_mut())(member, pos.x(), pos.y());
+                }
+            }
+            QEventType::Resize => {
+                if let Some(ref mut cb) = events.window.on_resize {
+                    let size = Ptr::from_raw(event as *const QEvent).static_downcast::<QResizeEvent>().size();
+                    (cb.as_mut())(member, cmp::max(0, size.width()) as u16, cmp::max(0, size.height()) as u16);
+                }
+            }
+            QEventType::Close => {
+                if let Some(ref key) = events.window.geometry_key {
+                    crate::geometry::save_remembered(&widget, key);
+                }
+            }
+            _ => {}
+        }
+    }
+}